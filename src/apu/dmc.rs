@@ -0,0 +1,152 @@
+use crate::{
+    cartridge::Cartridge,
+    memory::{Address, Byte, Memory, Word},
+};
+
+/// Timer periods in CPU cycles (NTSC).
+#[rustfmt::skip]
+const RATE_TABLE: [Word; 16] = [
+    428, 380, 340, 320, 286, 254, 226, 214, 190, 160, 142, 128, 106, 84, 72, 54,
+];
+
+/// The CPU is halted for this many cycles while the DMC fetches a sample byte.
+pub const FETCH_STALL: usize = 4;
+
+/// The delta modulation channel ($4010-$4013), which plays 1 bit delta
+/// encoded samples straight out of PRG memory.
+pub struct DMC {
+    pub irq: bool,
+    irq_enabled: bool,
+    looping: bool,
+    period: Word,
+    timer: Word,
+    output: Byte,
+    sample_address: Address,
+    sample_length: Word,
+    address: Address,
+    pub(super) bytes_remaining: Word,
+    buffer: Option<Byte>,
+    shift: Byte,
+    bits_remaining: Byte,
+    silence: bool,
+}
+
+impl DMC {
+    pub fn new() -> Self {
+        Self {
+            irq: false,
+            irq_enabled: false,
+            looping: false,
+            period: RATE_TABLE[0],
+            timer: 0,
+            output: 0,
+            sample_address: 0xC000,
+            sample_length: 1,
+            address: 0xC000,
+            bytes_remaining: 0,
+            buffer: None,
+            shift: 0,
+            bits_remaining: 8,
+            silence: true,
+        }
+    }
+
+    pub fn write(&mut self, register: u16, value: Byte) {
+        match register {
+            0 => {
+                // IL-- RRRR
+                self.irq_enabled = value & 0b1000_0000 != 0;
+                self.looping = value & 0b0100_0000 != 0;
+                self.period = RATE_TABLE[(value & 0x0F) as usize];
+                if !self.irq_enabled {
+                    self.irq = false;
+                }
+            }
+            1 => self.output = value & 0x7F,
+            2 => self.sample_address = 0xC000 | (value as Address) << 6,
+            _ => self.sample_length = (value as Word) << 4 | 1,
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.irq = false;
+        if !enabled {
+            self.bytes_remaining = 0;
+        } else if self.bytes_remaining == 0 {
+            self.restart();
+        }
+    }
+
+    fn restart(&mut self) {
+        self.address = self.sample_address;
+        self.bytes_remaining = self.sample_length;
+    }
+
+    /// Clocked every CPU cycle. Returns the number of cycles the CPU has to be
+    /// stalled for, if the memory reader fetched a byte.
+    pub fn clock(&mut self, cartridge: &mut Cartridge) -> usize {
+        let stall = self.fill_buffer(cartridge);
+
+        if self.timer > 0 {
+            self.timer -= 1;
+            return stall;
+        }
+        self.timer = self.period - 1;
+
+        if !self.silence {
+            if self.shift & 0x01 != 0 {
+                if self.output <= 125 {
+                    self.output += 2;
+                }
+            } else if self.output >= 2 {
+                self.output -= 2;
+            }
+        }
+        self.shift >>= 1;
+
+        self.bits_remaining -= 1;
+        if self.bits_remaining == 0 {
+            self.bits_remaining = 8;
+            match self.buffer.take() {
+                Some(byte) => {
+                    self.silence = false;
+                    self.shift = byte;
+                }
+                None => self.silence = true,
+            }
+        }
+
+        stall
+    }
+
+    fn fill_buffer(&mut self, cartridge: &mut Cartridge) -> usize {
+        if self.buffer.is_some() || self.bytes_remaining == 0 {
+            return 0;
+        }
+
+        self.buffer = Some(cartridge.read_byte(self.address));
+        // The address wraps around to $8000, not $0000.
+        self.address = self.address.checked_add(1).unwrap_or(0x8000);
+        self.bytes_remaining -= 1;
+
+        if self.bytes_remaining == 0 {
+            if self.looping {
+                self.restart();
+            } else if self.irq_enabled {
+                self.irq = true;
+            }
+        }
+
+        FETCH_STALL
+    }
+
+    pub fn output(&self) -> Byte {
+        self.output
+    }
+}
+
+impl Default for DMC {
+    fn default() -> Self {
+        Self::new()
+    }
+}
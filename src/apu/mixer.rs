@@ -0,0 +1,65 @@
+use crate::memory::Byte;
+
+/// Combines the channel outputs with the console's nonlinear DAC response,
+/// using the lookup tables from the NESdev wiki. The result is in 0.0..1.0.
+pub struct Mixer {
+    pulse_table: [f32; 31],
+    tnd_table: [f32; 203],
+}
+
+impl Mixer {
+    pub fn new() -> Self {
+        let mut pulse_table = [0.0; 31];
+        for (n, value) in pulse_table.iter_mut().enumerate().skip(1) {
+            *value = 95.52 / (8128.0 / n as f32 + 100.0);
+        }
+
+        let mut tnd_table = [0.0; 203];
+        for (n, value) in tnd_table.iter_mut().enumerate().skip(1) {
+            *value = 163.67 / (24329.0 / n as f32 + 100.0);
+        }
+
+        Self {
+            pulse_table,
+            tnd_table,
+        }
+    }
+
+    pub fn mix(&self, pulse1: Byte, pulse2: Byte, triangle: Byte, noise: Byte, dmc: Byte) -> f32 {
+        let pulse = pulse1 as usize + pulse2 as usize;
+        let tnd = 3 * triangle as usize + 2 * noise as usize + dmc as usize;
+        self.pulse_table[pulse] + self.tnd_table[tnd]
+    }
+}
+
+impl Default for Mixer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A first order high-pass filter, like the ones in the console's audio path.
+/// It also removes the DC offset of the mixer output.
+pub struct HighPass {
+    alpha: f32,
+    previous_input: f32,
+    previous_output: f32,
+}
+
+impl HighPass {
+    pub fn new(cutoff: f64, sample_rate: f64) -> Self {
+        let rc = 1.0 / (2.0 * std::f64::consts::PI * cutoff);
+        Self {
+            alpha: (rc / (rc + 1.0 / sample_rate)) as f32,
+            previous_input: 0.0,
+            previous_output: 0.0,
+        }
+    }
+
+    pub fn process(&mut self, input: f32) -> f32 {
+        let output = self.alpha * (self.previous_output + input - self.previous_input);
+        self.previous_input = input;
+        self.previous_output = output;
+        output
+    }
+}
@@ -0,0 +1,315 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter},
+    path::Path,
+};
+
+use crate::{
+    cartridge::Cartridge,
+    memory::{Address, Byte},
+};
+
+use self::{
+    dmc::DMC,
+    mixer::{HighPass, Mixer},
+    noise::Noise,
+    output::{SampleBuffer, WavWriter},
+    pulse::Pulse,
+    resampler::Resampler,
+    triangle::Triangle,
+};
+
+pub mod dmc;
+pub mod mixer;
+pub mod noise;
+pub mod output;
+pub mod pulse;
+pub mod resampler;
+pub mod triangle;
+pub mod units;
+
+/// The NTSC CPU clock, which drives the APU.
+pub const CPU_CLOCK_RATE: f64 = 1_789_773.0;
+pub const DEFAULT_SAMPLE_RATE: u32 = 44_100;
+
+/// Frame counter steps, in CPU cycles since the sequence (re)started.
+const QUARTER_FRAME_1: usize = 7457;
+const HALF_FRAME_1: usize = 14913;
+const QUARTER_FRAME_3: usize = 22371;
+const FOUR_STEP_IRQ: usize = 29828;
+const FOUR_STEP_LAST: usize = 29829;
+const FOUR_STEP_PERIOD: usize = 29830;
+const FIVE_STEP_LAST: usize = 37281;
+const FIVE_STEP_PERIOD: usize = 37282;
+
+pub struct APU {
+    pub pulse1: Pulse,
+    pub pulse2: Pulse,
+    pub triangle: Triangle,
+    pub noise: Noise,
+    pub dmc: DMC,
+    pub cycles: usize,
+    frame_cycle: usize,
+    five_step: bool,
+    irq_inhibit: bool,
+    frame_irq: bool,
+    /// Cycles until a $4017 write restarts the frame sequence.
+    frame_reset: Option<usize>,
+    stall: usize,
+    mixer: Mixer,
+    level: f32,
+    resampler: Resampler,
+    high_pass: HighPass,
+    sample_rate: u32,
+    /// Mixed audio at the host sample rate, ready to be drained.
+    pub samples: SampleBuffer,
+    wav: Option<WavWriter<BufWriter<File>>>,
+}
+
+impl APU {
+    pub const STATUS_PULSE1: u8 = 1 << 0;
+    pub const STATUS_PULSE2: u8 = 1 << 1;
+    pub const STATUS_TRIANGLE: u8 = 1 << 2;
+    pub const STATUS_NOISE: u8 = 1 << 3;
+    pub const STATUS_DMC: u8 = 1 << 4;
+    pub const STATUS_FRAME_IRQ: u8 = 1 << 6;
+    pub const STATUS_DMC_IRQ: u8 = 1 << 7;
+
+    pub const FRAME_FIVE_STEP: u8 = 1 << 7;
+    pub const FRAME_IRQ_INHIBIT: u8 = 1 << 6;
+
+    pub fn new() -> Self {
+        Self {
+            pulse1: Pulse::new(true),
+            pulse2: Pulse::new(false),
+            triangle: Triangle::new(),
+            noise: Noise::new(),
+            dmc: DMC::new(),
+            cycles: 0,
+            frame_cycle: 0,
+            five_step: false,
+            irq_inhibit: false,
+            frame_irq: false,
+            frame_reset: None,
+            stall: 0,
+            mixer: Mixer::new(),
+            level: 0.0,
+            resampler: Resampler::new(CPU_CLOCK_RATE, DEFAULT_SAMPLE_RATE as f64),
+            high_pass: HighPass::new(90.0, DEFAULT_SAMPLE_RATE as f64),
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            samples: SampleBuffer::new(DEFAULT_SAMPLE_RATE as usize / 2),
+            wav: None,
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Changes the host sample rate, e.g. to 48kHz. Buffered samples are dropped.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate;
+        self.resampler = Resampler::new(CPU_CLOCK_RATE, sample_rate as f64);
+        self.high_pass = HighPass::new(90.0, sample_rate as f64);
+        self.samples = SampleBuffer::new(sample_rate as usize / 2);
+        self.level = 0.0;
+    }
+
+    /// Writes every sample produced from now on to a 16 bit mono WAV file.
+    pub fn dump_wav<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        self.wav = Some(WavWriter::new(file, self.sample_rate)?);
+        Ok(())
+    }
+
+    /// Finishes and closes the WAV file started by `dump_wav`, if any.
+    pub fn stop_wav(&mut self) -> io::Result<()> {
+        match self.wav.take() {
+            Some(mut wav) => wav.finish(),
+            None => Ok(()),
+        }
+    }
+
+    /// The current output of all channels, mixed, before resampling.
+    pub fn output(&self) -> f32 {
+        self.mixer.mix(
+            self.pulse1.output(),
+            self.pulse2.output(),
+            self.triangle.output(),
+            self.noise.output(),
+            self.dmc.output(),
+        )
+    }
+
+    /// Whether the frame counter or the DMC is asserting the IRQ line.
+    pub fn irq(&self) -> bool {
+        self.frame_irq || self.dmc.irq
+    }
+
+    /// Returns (and forgets) the CPU cycles stolen by DMC sample fetches.
+    pub fn take_stall(&mut self) -> usize {
+        std::mem::take(&mut self.stall)
+    }
+
+    /// Only $4015 is readable, everything else returns the open bus.
+    pub fn read_register(&mut self, address: Address) -> Byte {
+        if address != 0x4015 {
+            return 0;
+        }
+
+        let mut status = 0;
+        for (active, flag) in [
+            (self.pulse1.length.active(), Self::STATUS_PULSE1),
+            (self.pulse2.length.active(), Self::STATUS_PULSE2),
+            (self.triangle.length.active(), Self::STATUS_TRIANGLE),
+            (self.noise.length.active(), Self::STATUS_NOISE),
+            (self.dmc.bytes_remaining > 0, Self::STATUS_DMC),
+            (self.frame_irq, Self::STATUS_FRAME_IRQ),
+            (self.dmc.irq, Self::STATUS_DMC_IRQ),
+        ] {
+            if active {
+                status |= flag;
+            }
+        }
+
+        self.frame_irq = false;
+        status
+    }
+
+    pub fn write_register(&mut self, address: Address, value: Byte) {
+        let register = address & 0x03;
+        match address {
+            0x4000..=0x4003 => self.pulse1.write(register, value),
+            0x4004..=0x4007 => self.pulse2.write(register, value),
+            0x4008..=0x400B => self.triangle.write(register, value),
+            0x400C..=0x400F => self.noise.write(register, value),
+            0x4010..=0x4013 => self.dmc.write(register, value),
+            0x4015 => {
+                self.pulse1.length.set_enabled(value & Self::STATUS_PULSE1 != 0);
+                self.pulse2.length.set_enabled(value & Self::STATUS_PULSE2 != 0);
+                self.triangle.length.set_enabled(value & Self::STATUS_TRIANGLE != 0);
+                self.noise.length.set_enabled(value & Self::STATUS_NOISE != 0);
+                self.dmc.set_enabled(value & Self::STATUS_DMC != 0);
+            }
+            0x4017 => {
+                self.five_step = value & Self::FRAME_FIVE_STEP != 0;
+                self.irq_inhibit = value & Self::FRAME_IRQ_INHIBIT != 0;
+                if self.irq_inhibit {
+                    self.frame_irq = false;
+                }
+
+                // The sequence restarts 3 or 4 cycles later, depending on
+                // whether the write lands on an even or odd cycle.
+                self.frame_reset = Some(if self.cycles % 2 == 1 { 4 } else { 3 });
+                if self.five_step {
+                    self.clock_quarter_frame();
+                    self.clock_half_frame();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Advances the APU by the given amount of CPU cycles.
+    pub fn tick(&mut self, cycles: usize, cartridge: &mut Cartridge) {
+        for _ in 0..cycles {
+            self.tick_cycle(cartridge);
+        }
+
+        let (samples, high_pass, wav) = (&mut self.samples, &mut self.high_pass, &mut self.wav);
+        self.resampler.read(|sample| {
+            let sample = high_pass.process(sample);
+            samples.push(sample);
+            if let Some(writer) = wav {
+                if let Err(error) = writer.write_sample(sample) {
+                    eprintln!("Failed to write WAV file: {}", error);
+                    *wav = None;
+                }
+            }
+        });
+    }
+
+    fn tick_cycle(&mut self, cartridge: &mut Cartridge) {
+        // Pulse timers run at half the CPU rate, on "APU cycles".
+        if self.cycles % 2 == 1 {
+            self.pulse1.clock_timer();
+            self.pulse2.clock_timer();
+        }
+        self.triangle.clock_timer();
+        self.noise.clock_timer();
+        self.stall += self.dmc.clock(cartridge);
+
+        self.clock_frame_counter();
+
+        let level = self.output();
+        if level != self.level {
+            self.resampler.add_delta(level - self.level);
+            self.level = level;
+        }
+        self.resampler.clock(1);
+
+        self.cycles += 1;
+    }
+
+    fn clock_frame_counter(&mut self) {
+        if let Some(delay) = self.frame_reset {
+            if delay == 0 {
+                self.frame_reset = None;
+                self.frame_cycle = 0;
+            } else {
+                self.frame_reset = Some(delay - 1);
+            }
+        }
+
+        self.frame_cycle += 1;
+        match (self.frame_cycle, self.five_step) {
+            (QUARTER_FRAME_1, _) | (QUARTER_FRAME_3, _) => self.clock_quarter_frame(),
+            (HALF_FRAME_1, _) | (FIVE_STEP_LAST, true) => {
+                self.clock_quarter_frame();
+                self.clock_half_frame();
+            }
+            (FOUR_STEP_IRQ, false) => self.raise_frame_irq(),
+            (FOUR_STEP_LAST, false) => {
+                self.raise_frame_irq();
+                self.clock_quarter_frame();
+                self.clock_half_frame();
+            }
+            (FOUR_STEP_PERIOD, false) => {
+                self.raise_frame_irq();
+                self.frame_cycle = 0;
+            }
+            (FIVE_STEP_PERIOD, true) => self.frame_cycle = 0,
+            _ => {}
+        }
+    }
+
+    fn raise_frame_irq(&mut self) {
+        if !self.irq_inhibit {
+            self.frame_irq = true;
+        }
+    }
+
+    /// Clocks the envelopes and the triangle's linear counter.
+    fn clock_quarter_frame(&mut self) {
+        self.pulse1.envelope.clock();
+        self.pulse2.envelope.clock();
+        self.noise.envelope.clock();
+        self.triangle.clock_linear_counter();
+    }
+
+    /// Clocks the length counters and the sweep units.
+    fn clock_half_frame(&mut self) {
+        self.pulse1.length.clock();
+        self.pulse2.length.clock();
+        self.triangle.length.clock();
+        self.noise.length.clock();
+        self.pulse1.clock_sweep();
+        self.pulse2.clock_sweep();
+    }
+}
+
+impl Default for APU {
+    fn default() -> Self {
+        Self::new()
+    }
+}
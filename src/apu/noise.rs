@@ -0,0 +1,81 @@
+use crate::memory::{Byte, Word};
+
+use super::units::{Envelope, LengthCounter};
+
+/// Timer periods in CPU cycles (NTSC).
+#[rustfmt::skip]
+const PERIOD_TABLE: [Word; 16] = [
+    4, 8, 16, 32, 64, 96, 128, 160, 202, 254, 380, 508, 762, 1016, 2034, 4068,
+];
+
+/// The pseudo-random noise channel ($400C-$400F).
+pub struct Noise {
+    pub length: LengthCounter,
+    pub envelope: Envelope,
+    /// Short mode taps bit 6 instead of bit 1, giving a 93 step metallic loop.
+    short_mode: bool,
+    shift: Word,
+    period: Word,
+    timer: Word,
+}
+
+impl Noise {
+    pub fn new() -> Self {
+        Self {
+            length: LengthCounter::new(),
+            envelope: Envelope::new(),
+            short_mode: false,
+            shift: 1,
+            period: PERIOD_TABLE[0],
+            timer: 0,
+        }
+    }
+
+    pub fn write(&mut self, register: u16, value: Byte) {
+        match register {
+            0 => {
+                // --LC VVVV
+                self.length.halt = value & 0b0010_0000 != 0;
+                self.envelope.write(value);
+            }
+            1 => {}
+            2 => {
+                // M--- PPPP
+                self.short_mode = value & 0b1000_0000 != 0;
+                self.period = PERIOD_TABLE[(value & 0x0F) as usize];
+            }
+            _ => {
+                // LLLL L---
+                self.length.load(value >> 3);
+                self.envelope.start = true;
+            }
+        }
+    }
+
+    /// Clocked every CPU cycle.
+    pub fn clock_timer(&mut self) {
+        if self.timer > 0 {
+            self.timer -= 1;
+            return;
+        }
+
+        self.timer = self.period - 1;
+        let tap = if self.short_mode { 6 } else { 1 };
+        let feedback = (self.shift ^ (self.shift >> tap)) & 0x01;
+        self.shift = (self.shift >> 1) | (feedback << 14);
+    }
+
+    pub fn output(&self) -> Byte {
+        if !self.length.active() || self.shift & 0x01 != 0 {
+            0
+        } else {
+            self.envelope.output()
+        }
+    }
+}
+
+impl Default for Noise {
+    fn default() -> Self {
+        Self::new()
+    }
+}
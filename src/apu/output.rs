@@ -0,0 +1,127 @@
+use std::{
+    collections::VecDeque,
+    io::{self, Seek, SeekFrom, Write},
+};
+
+/// A bounded FIFO of mono samples in -1.0..1.0 for a frontend to drain.
+/// When the frontend falls behind, the oldest samples are dropped.
+pub struct SampleBuffer {
+    samples: VecDeque<f32>,
+    capacity: usize,
+}
+
+impl SampleBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, sample: f32) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Moves up to `out.len()` samples into `out`, returning how many.
+    pub fn drain_f32(&mut self, out: &mut [f32]) -> usize {
+        let count = out.len().min(self.samples.len());
+        for (slot, sample) in out.iter_mut().zip(self.samples.drain(..count)) {
+            *slot = sample;
+        }
+        count
+    }
+
+    /// Like `drain_f32`, converted to signed 16 bit.
+    pub fn drain_i16(&mut self, out: &mut [i16]) -> usize {
+        let count = out.len().min(self.samples.len());
+        for (slot, sample) in out.iter_mut().zip(self.samples.drain(..count)) {
+            *slot = to_i16(sample);
+        }
+        count
+    }
+}
+
+pub fn to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
+
+/// Writes 16 bit mono PCM to a RIFF/WAVE stream. The chunk sizes are
+/// patched in by `finish`, which also runs on drop.
+pub struct WavWriter<W: Write + Seek> {
+    writer: W,
+    samples: u32,
+    finished: bool,
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    const HEADER_SIZE: u32 = 44;
+
+    pub fn new(mut writer: W, sample_rate: u32) -> io::Result<Self> {
+        let channels: u16 = 1;
+        let bits: u16 = 16;
+        let block_align = channels * bits / 8;
+
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&0u32.to_le_bytes())?; // Patched by finish().
+        writer.write_all(b"WAVE")?;
+        writer.write_all(b"fmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        writer.write_all(&1u16.to_le_bytes())?; // PCM.
+        writer.write_all(&channels.to_le_bytes())?;
+        writer.write_all(&sample_rate.to_le_bytes())?;
+        writer.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+        writer.write_all(&block_align.to_le_bytes())?;
+        writer.write_all(&bits.to_le_bytes())?;
+        writer.write_all(b"data")?;
+        writer.write_all(&0u32.to_le_bytes())?; // Patched by finish().
+
+        Ok(Self {
+            writer,
+            samples: 0,
+            finished: false,
+        })
+    }
+
+    pub fn write_sample(&mut self, sample: f32) -> io::Result<()> {
+        self.samples += 1;
+        self.writer.write_all(&to_i16(sample).to_le_bytes())
+    }
+
+    pub fn finish(&mut self) -> io::Result<()> {
+        if self.finished {
+            return Ok(());
+        }
+        self.finished = true;
+
+        let data_size = self.samples * 2;
+        self.writer.seek(SeekFrom::Start(4))?;
+        self.writer.write_all(&(Self::HEADER_SIZE - 8 + data_size).to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(40))?;
+        self.writer.write_all(&data_size.to_le_bytes())?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.flush()
+    }
+}
+
+impl<W: Write + Seek> Drop for WavWriter<W> {
+    fn drop(&mut self) {
+        if let Err(error) = self.finish() {
+            eprintln!("Failed to finish WAV file: {}", error);
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use crate::memory::{Byte, Word};
+
+use super::units::{Envelope, LengthCounter};
+
+#[rustfmt::skip]
+const DUTY_TABLE: [[Byte; 8]; 4] = [
+    [0, 1, 0, 0, 0, 0, 0, 0], // 12.5%
+    [0, 1, 1, 0, 0, 0, 0, 0], // 25%
+    [0, 1, 1, 1, 1, 0, 0, 0], // 50%
+    [1, 0, 0, 1, 1, 1, 1, 1], // 25% negated
+];
+
+/// One of the two square wave channels ($4000-$4003 and $4004-$4007).
+pub struct Pulse {
+    pub length: LengthCounter,
+    pub envelope: Envelope,
+    duty: Byte,
+    step: Byte,
+    period: Word,
+    timer: Word,
+    sweep: Sweep,
+    /// Pulse 1 negates with ones' complement, pulse 2 with two's complement.
+    ones_complement: bool,
+}
+
+struct Sweep {
+    enabled: bool,
+    period: Byte,
+    negate: bool,
+    shift: Byte,
+    divider: Byte,
+    reload: bool,
+}
+
+impl Pulse {
+    pub fn new(ones_complement: bool) -> Self {
+        Self {
+            length: LengthCounter::new(),
+            envelope: Envelope::new(),
+            duty: 0,
+            step: 0,
+            period: 0,
+            timer: 0,
+            sweep: Sweep {
+                enabled: false,
+                period: 0,
+                negate: false,
+                shift: 0,
+                divider: 0,
+                reload: false,
+            },
+            ones_complement,
+        }
+    }
+
+    /// Writes one of the four registers, `register` being the address & 3.
+    pub fn write(&mut self, register: u16, value: Byte) {
+        match register {
+            0 => {
+                // DDLC VVVV
+                self.duty = value >> 6;
+                self.length.halt = value & 0b0010_0000 != 0;
+                self.envelope.write(value);
+            }
+            1 => {
+                // EPPP NSSS
+                self.sweep.enabled = value & 0b1000_0000 != 0;
+                self.sweep.period = (value >> 4) & 0x07;
+                self.sweep.negate = value & 0b0000_1000 != 0;
+                self.sweep.shift = value & 0x07;
+                self.sweep.reload = true;
+            }
+            2 => self.period = (self.period & 0x0700) | value as Word,
+            _ => {
+                // LLLL LTTT
+                self.period = (self.period & 0x00FF) | ((value as Word & 0x07) << 8);
+                self.length.load(value >> 3);
+                self.envelope.start = true;
+                self.step = 0;
+            }
+        }
+    }
+
+    /// Clocked every other CPU cycle.
+    pub fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.period;
+            self.step = (self.step + 1) & 0x07;
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    /// Clocked on every half frame, along with the length counter.
+    pub fn clock_sweep(&mut self) {
+        let target = self.target_period();
+        if self.sweep.divider == 0 && self.sweep.enabled && self.sweep.shift > 0 && !self.muted(target) {
+            self.period = target;
+        }
+
+        if self.sweep.divider == 0 || self.sweep.reload {
+            self.sweep.divider = self.sweep.period;
+            self.sweep.reload = false;
+        } else {
+            self.sweep.divider -= 1;
+        }
+    }
+
+    fn target_period(&self) -> Word {
+        let change = self.period >> self.sweep.shift;
+        if self.sweep.negate {
+            let change = change + self.ones_complement as Word;
+            self.period.saturating_sub(change)
+        } else {
+            self.period + change
+        }
+    }
+
+    /// The sweep unit mutes the channel even when it is disabled.
+    fn muted(&self, target: Word) -> bool {
+        self.period < 8 || target > 0x07FF
+    }
+
+    pub fn output(&self) -> Byte {
+        if !self.length.active()
+            || self.muted(self.target_period())
+            || DUTY_TABLE[self.duty as usize][self.step as usize] == 0
+        {
+            0
+        } else {
+            self.envelope.output()
+        }
+    }
+}
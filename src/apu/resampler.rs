@@ -0,0 +1,95 @@
+use std::f64::consts::PI;
+
+/// Taps of the band-limited step, and the number of sub-sample phases it is
+/// precomputed for.
+const WIDTH: usize = 16;
+const PHASES: usize = 64;
+
+/// Passband edge, as a fraction of the output rate (Nyquist being 0.5).
+const CUTOFF: f64 = 0.45;
+
+/// Converts a signal clocked at the CPU rate to the host sample rate.
+///
+/// Instead of point sampling, every change of the input level is added to
+/// the output as a band-limited step (a windowed sinc impulse, integrated),
+/// so square waves don't alias into audible garbage. This is the approach
+/// popularized by blip_buf.
+pub struct Resampler {
+    /// Output samples per input clock.
+    step: f64,
+    /// Position of the current clock in output samples, from the buffer start.
+    position: f64,
+    /// Band-limited impulses of the level changes, integrated on the way out.
+    deltas: Vec<f32>,
+    level: f32,
+    kernel: Vec<[f32; WIDTH]>,
+}
+
+impl Resampler {
+    pub fn new(clock_rate: f64, sample_rate: f64) -> Self {
+        Self {
+            step: sample_rate / clock_rate,
+            position: 0.0,
+            deltas: vec![0.0; WIDTH],
+            level: 0.0,
+            kernel: (0..PHASES).map(|phase| impulse(phase as f64 / PHASES as f64)).collect(),
+        }
+    }
+
+    /// Advances the input by the given amount of clocks.
+    pub fn clock(&mut self, clocks: usize) {
+        self.position += clocks as f64 * self.step;
+    }
+
+    /// Adds a change of the input level at the current clock.
+    pub fn add_delta(&mut self, delta: f32) {
+        let start = self.position as usize;
+        let phase = ((self.position - start as f64) * PHASES as f64) as usize;
+
+        if self.deltas.len() < start + WIDTH {
+            self.deltas.resize(start + WIDTH, 0.0);
+        }
+
+        for (slot, tap) in self.deltas[start..start + WIDTH].iter_mut().zip(self.kernel[phase].iter()) {
+            *slot += delta * tap;
+        }
+    }
+
+    /// Passes every completed output sample to `output`. Samples before the
+    /// current position can't be changed by later deltas anymore.
+    pub fn read<F: FnMut(f32)>(&mut self, mut output: F) {
+        let complete = self.position as usize;
+        if self.deltas.len() < complete {
+            self.deltas.resize(complete, 0.0);
+        }
+
+        for delta in self.deltas.drain(..complete) {
+            self.level += delta;
+            output(self.level);
+        }
+
+        self.position -= complete as f64;
+    }
+}
+
+/// A windowed sinc impulse delayed by `offset` samples (0.0..1.0), centered
+/// in the kernel and normalized to a DC gain of 1 so steps settle exactly.
+fn impulse(offset: f64) -> [f32; WIDTH] {
+    let half = WIDTH as f64 / 2.0;
+    let mut taps = [0.0; WIDTH];
+
+    for (k, tap) in taps.iter_mut().enumerate() {
+        let x = k as f64 - (half - 1.0) - offset;
+        let sinc = if x == 0.0 {
+            1.0
+        } else {
+            (2.0 * PI * CUTOFF * x).sin() / (2.0 * PI * CUTOFF * x)
+        };
+        // Blackman window over -half..half.
+        let window = 0.42 + 0.5 * (PI * x / half).cos() + 0.08 * (2.0 * PI * x / half).cos();
+        *tap = sinc * window;
+    }
+
+    let sum: f64 = taps.iter().sum();
+    taps.map(|tap| (tap / sum) as f32)
+}
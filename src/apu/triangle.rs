@@ -0,0 +1,93 @@
+use crate::memory::{Byte, Word};
+
+use super::units::LengthCounter;
+
+#[rustfmt::skip]
+const SEQUENCE: [Byte; 32] = [
+    15, 14, 13, 12, 11, 10,  9,  8,  7,  6,  5,  4,  3,  2,  1,  0,
+     0,  1,  2,  3,  4,  5,  6,  7,  8,  9, 10, 11, 12, 13, 14, 15,
+];
+
+/// The triangle wave channel ($4008-$400B).
+pub struct Triangle {
+    pub length: LengthCounter,
+    /// Doubles as the length counter halt flag.
+    control: bool,
+    linear_reload_value: Byte,
+    linear_counter: Byte,
+    linear_reload: bool,
+    step: Byte,
+    period: Word,
+    timer: Word,
+}
+
+impl Triangle {
+    pub fn new() -> Self {
+        Self {
+            length: LengthCounter::new(),
+            control: false,
+            linear_reload_value: 0,
+            linear_counter: 0,
+            linear_reload: false,
+            step: 0,
+            period: 0,
+            timer: 0,
+        }
+    }
+
+    pub fn write(&mut self, register: u16, value: Byte) {
+        match register {
+            0 => {
+                // CRRR RRRR
+                self.control = value & 0b1000_0000 != 0;
+                self.length.halt = self.control;
+                self.linear_reload_value = value & 0x7F;
+            }
+            1 => {}
+            2 => self.period = (self.period & 0x0700) | value as Word,
+            _ => {
+                // LLLL LTTT
+                self.period = (self.period & 0x00FF) | ((value as Word & 0x07) << 8);
+                self.length.load(value >> 3);
+                self.linear_reload = true;
+            }
+        }
+    }
+
+    /// Clocked every CPU cycle. The sequencer only advances while both the
+    /// linear counter and the length counter are non-zero.
+    pub fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.period;
+            if self.linear_counter > 0 && self.length.active() {
+                self.step = (self.step + 1) & 0x1F;
+            }
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    /// Clocked on every quarter frame.
+    pub fn clock_linear_counter(&mut self) {
+        if self.linear_reload {
+            self.linear_counter = self.linear_reload_value;
+        } else if self.linear_counter > 0 {
+            self.linear_counter -= 1;
+        }
+
+        if !self.control {
+            self.linear_reload = false;
+        }
+    }
+
+    /// Silencing the channel would pop, so it keeps outputting its last step.
+    pub fn output(&self) -> Byte {
+        SEQUENCE[self.step as usize]
+    }
+}
+
+impl Default for Triangle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
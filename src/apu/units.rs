@@ -0,0 +1,117 @@
+use crate::memory::Byte;
+
+#[rustfmt::skip]
+const LENGTH_TABLE: [Byte; 32] = [
+    10, 254, 20,  2, 40,  4, 80,  6, 160,  8, 60, 10, 14, 12, 26, 14,
+    12,  16, 24, 18, 48, 20, 96, 22, 192, 24, 72, 26, 16, 28, 32, 30,
+];
+
+/// Silences a channel after a given number of half frames, unless halted.
+pub struct LengthCounter {
+    pub value: Byte,
+    pub halt: bool,
+    enabled: bool,
+}
+
+impl LengthCounter {
+    pub fn new() -> Self {
+        Self {
+            value: 0,
+            halt: false,
+            enabled: false,
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.value = 0;
+        }
+    }
+
+    /// Loads the counter from the 5 bit index written to the channel's last register.
+    pub fn load(&mut self, index: Byte) {
+        if self.enabled {
+            self.value = LENGTH_TABLE[(index & 0x1F) as usize];
+        }
+    }
+
+    /// Clocked on every half frame.
+    pub fn clock(&mut self) {
+        if !self.halt && self.value > 0 {
+            self.value -= 1;
+        }
+    }
+
+    pub fn active(&self) -> bool {
+        self.value > 0
+    }
+}
+
+impl Default for LengthCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Produces either a constant volume or a decaying (optionally looping) sawtooth.
+pub struct Envelope {
+    pub start: bool,
+    pub looping: bool,
+    pub constant: bool,
+    pub volume: Byte,
+    divider: Byte,
+    decay: Byte,
+}
+
+impl Envelope {
+    pub fn new() -> Self {
+        Self {
+            start: false,
+            looping: false,
+            constant: false,
+            volume: 0,
+            divider: 0,
+            decay: 0,
+        }
+    }
+
+    /// Handles the `--LC VVVV` bits shared by the pulse and noise channels.
+    pub fn write(&mut self, value: Byte) {
+        self.looping = value & 0b0010_0000 != 0;
+        self.constant = value & 0b0001_0000 != 0;
+        self.volume = value & 0x0F;
+    }
+
+    /// Clocked on every quarter frame.
+    pub fn clock(&mut self) {
+        if self.start {
+            self.start = false;
+            self.decay = 15;
+            self.divider = self.volume;
+        } else if self.divider == 0 {
+            self.divider = self.volume;
+            if self.decay > 0 {
+                self.decay -= 1;
+            } else if self.looping {
+                self.decay = 15;
+            }
+        } else {
+            self.divider -= 1;
+        }
+    }
+
+    pub fn output(&self) -> Byte {
+        if self.constant {
+            self.volume
+        } else {
+            self.decay
+        }
+    }
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,141 @@
+use crate::{
+    apu::APU,
+    cartridge::Cartridge,
+    controller::Controller,
+    memory::{ram::RAM, Address, Byte, Memory},
+    ppu::PPU,
+};
+
+/// Everything the CPU needs from the system it is plugged into: memory
+/// accesses, plus a clock for the other chips and the interrupt lines.
+/// The defaults describe a system with nothing but memory on the bus.
+pub trait CpuBus: Memory {
+    /// Advances the rest of the system by the given amount of CPU cycles.
+    /// Returns the number of extra cycles the CPU was halted for meanwhile
+    /// (e.g. by DMA), which have been ticked already.
+    fn tick(&mut self, _cycles: usize) -> usize {
+        0
+    }
+
+    /// Returns `true` once for every edge on the NMI line.
+    fn poll_nmi(&mut self) -> bool {
+        false
+    }
+
+    /// Whether the IRQ line is asserted.
+    fn irq(&self) -> bool {
+        false
+    }
+}
+
+/// The NES CPU address space:
+///
+/// - $0000-$1FFF: 2KB of internal RAM, mirrored
+/// - $2000-$3FFF: PPU registers, mirrored every 8 bytes
+/// - $4000-$4017: APU, OAM DMA and controller registers
+/// - $4020-$FFFF: cartridge
+pub struct Bus {
+    pub ram: RAM,
+    pub ppu: PPU,
+    pub apu: APU,
+    pub controllers: [Controller; 2],
+    pub cartridge: Cartridge,
+    /// CPU cycles seen by the bus, used to align DMA.
+    pub cycles: usize,
+    oam_dma: bool,
+}
+
+impl Bus {
+    pub fn new(cartridge: Cartridge) -> Self {
+        Self {
+            ram: RAM::new(),
+            ppu: PPU::new(),
+            apu: APU::new(),
+            controllers: [Controller::new(), Controller::new()],
+            cartridge,
+            cycles: 0,
+            oam_dma: false,
+        }
+    }
+
+    /// Copies page $XX00-$XXFF to the PPU OAM. The CPU halt is accounted for
+    /// on the next `tick`, once the cycle parity of the write is known.
+    fn oam_dma(&mut self, page: Byte) {
+        let base = (page as Address) << 8;
+        for offset in 0x00..=0xFF {
+            let value = self.read_byte(base | offset);
+            self.ppu.write_register(0x2004, value, &mut self.cartridge);
+        }
+
+        self.oam_dma = true;
+    }
+
+    fn tick_devices(&mut self, cycles: usize) {
+        let mut cycles = cycles;
+        while cycles > 0 {
+            self.ppu.tick(cycles * 3, &mut self.cartridge);
+            self.apu.tick(cycles, &mut self.cartridge);
+            self.cycles += cycles;
+
+            // DMC sample fetches halt the CPU, but the rest of the system keeps going.
+            cycles = self.apu.take_stall();
+        }
+    }
+}
+
+impl Memory for Bus {
+    fn read_byte(&mut self, address: Address) -> Byte {
+        match address {
+            0x0000..=0x1fff => self.ram.read_byte(address),
+            0x2000..=0x3fff => self.ppu.read_register(address, &mut self.cartridge),
+            0x4015 => self.apu.read_register(address),
+            // Only bit 0 is driven, the upper bits keep the open bus value,
+            // which is the high byte of the address.
+            0x4016 => 0x40 | self.controllers[0].read(),
+            0x4017 => 0x40 | self.controllers[1].read(),
+            0x4020..=0xffff => self.cartridge.read_byte(address),
+            // Write-only and unused registers: nothing drives the data bus, so
+            // it keeps the high byte of the address, like $4016/$4017 above.
+            _ => (address >> 8) as Byte,
+        }
+    }
+
+    fn write_byte(&mut self, address: Address, value: Byte) {
+        match address {
+            0x0000..=0x1fff => self.ram.write_byte(address, value),
+            0x2000..=0x3fff => self.ppu.write_register(address, value, &mut self.cartridge),
+            0x4000..=0x4013 | 0x4015 | 0x4017 => self.apu.write_register(address, value),
+            0x4014 => self.oam_dma(value),
+            0x4016 => {
+                for controller in self.controllers.iter_mut() {
+                    controller.write(value);
+                }
+            }
+            0x4020..=0xffff => self.cartridge.write_byte(address, value),
+            _ => {}
+        }
+    }
+}
+
+impl CpuBus for Bus {
+    fn tick(&mut self, cycles: usize) -> usize {
+        let start = self.cycles;
+        self.tick_devices(cycles);
+
+        if std::mem::take(&mut self.oam_dma) {
+            // 513 cycles, plus one to align with the APU when starting on an odd cycle.
+            let dma = if self.cycles % 2 == 1 { 514 } else { 513 };
+            self.tick_devices(dma);
+        }
+
+        self.cycles - start - cycles
+    }
+
+    fn poll_nmi(&mut self) -> bool {
+        self.ppu.poll_nmi()
+    }
+
+    fn irq(&self) -> bool {
+        self.cartridge.irq() || self.apu.irq()
+    }
+}
@@ -0,0 +1,70 @@
+use std::{error::Error, fmt};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum CartridgeError {
+    /// The file does not start with "NES\x1A".
+    BadMagic,
+    /// The file is shorter than the 16 byte header.
+    TruncatedHeader {
+        size: usize,
+    },
+    TruncatedTrainer {
+        expected: usize,
+        actual: usize,
+    },
+    TruncatedPrg {
+        expected: usize,
+        actual: usize,
+    },
+    TruncatedChr {
+        expected: usize,
+        actual: usize,
+    },
+    UnsupportedMapper {
+        mapper: u16,
+        submapper: u8,
+    },
+    /// A save file does not match the size of the cartridge's PRG-RAM.
+    SaveSizeMismatch { expected: usize, actual: usize },
+    /// The header is well-formed but describes something we can't load.
+    UnsupportedFormat(&'static str),
+}
+
+impl fmt::Display for CartridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CartridgeError::BadMagic => write!(f, "File is not in iNES file format"),
+            CartridgeError::TruncatedHeader { size } => {
+                write!(f, "Header is truncated: expected 16 bytes, found {}", size)
+            }
+            CartridgeError::TruncatedTrainer { expected, actual } => write!(
+                f,
+                "Trainer is truncated: expected {} bytes, found {}",
+                expected, actual
+            ),
+            CartridgeError::TruncatedPrg { expected, actual } => write!(
+                f,
+                "PRG-ROM is truncated: expected {} bytes, found {}",
+                expected, actual
+            ),
+            CartridgeError::TruncatedChr { expected, actual } => write!(
+                f,
+                "CHR-ROM is truncated: expected {} bytes, found {}",
+                expected, actual
+            ),
+            CartridgeError::UnsupportedMapper { mapper, submapper } => {
+                write!(f, "Mapper {}.{} is not supported", mapper, submapper)
+            }
+            CartridgeError::SaveSizeMismatch { expected, actual } => write!(
+                f,
+                "Save file has the wrong size: expected {} bytes, found {}",
+                expected, actual
+            ),
+            CartridgeError::UnsupportedFormat(reason) => {
+                write!(f, "Unsupported format: {}", reason)
+            }
+        }
+    }
+}
+
+impl Error for CartridgeError {}
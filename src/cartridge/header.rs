@@ -0,0 +1,212 @@
+use super::{CartridgeError, Mirroring};
+
+pub const HEADER_SIZE: usize = 16;
+pub const TRAINER_SIZE: usize = 512;
+
+const NES_TAG: [u8; 4] = [0x4E, 0x45, 0x53, 0x1A];
+const PRG_SIZE: usize = 16384;
+const CHR_SIZE: usize = 8192;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Format {
+    INes,
+    Nes2,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Timing {
+    Ntsc,
+    Pal,
+    MultiRegion,
+    Dendy,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ConsoleType {
+    Nes,
+    VsSystem { ppu: u8, hardware: u8 },
+    Playchoice10,
+    Extended(u8),
+}
+
+/// The iNES / NES 2.0 header found in the first 16 bytes of a ROM file.
+/// Memory sizes are in bytes.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CartridgeHeader {
+    pub format: Format,
+    pub mapper: u16,
+    pub submapper: u8,
+    pub prg_rom_size: usize,
+    pub chr_rom_size: usize,
+    pub prg_ram_size: usize,
+    pub prg_nvram_size: usize,
+    pub chr_ram_size: usize,
+    pub chr_nvram_size: usize,
+    pub mirroring: Mirroring,
+    pub battery: bool,
+    pub trainer: bool,
+    pub timing: Timing,
+    pub console: ConsoleType,
+    pub misc_roms: u8,
+    pub expansion_device: u8,
+}
+
+impl CartridgeHeader {
+    /// Parses the header at the start of `raw`, which must be at least
+    /// `HEADER_SIZE` bytes long. The ROM data after it is not checked.
+    pub fn parse(raw: &[u8]) -> Result<CartridgeHeader, CartridgeError> {
+        if !raw.starts_with(&NES_TAG[..raw.len().min(NES_TAG.len())]) {
+            return Err(CartridgeError::BadMagic);
+        }
+
+        if raw.len() < HEADER_SIZE {
+            return Err(CartridgeError::TruncatedHeader { size: raw.len() });
+        }
+
+        let four_screen = raw[6] & 0b1000 != 0;
+        let vertical_mirroring = raw[6] & 0b1 != 0;
+        let mirroring = match (four_screen, vertical_mirroring) {
+            (true, _) => Mirroring::FourScreen,
+            (false, true) => Mirroring::Vertical,
+            (false, false) => Mirroring::Horizontal,
+        };
+
+        let battery = raw[6] & 0b10 != 0;
+        let trainer = raw[6] & 0b100 != 0;
+
+        match (raw[7] >> 2) & 0b11 {
+            0b10 => Self::parse_nes2(raw, mirroring, battery, trainer),
+            ines_ver => {
+                // Archaic dumps have garbage (e.g. "DiskDude!") from byte 7 on.
+                let archaic = ines_ver != 0 || raw[12..16].iter().any(|&byte| byte != 0);
+                let mapper_high = if archaic { 0 } else { raw[7] & 0b1111_0000 };
+
+                let chr_rom_size = raw[5] as usize * CHR_SIZE;
+                let prg_ram_size = match raw[8] {
+                    0 => 8192, // A value of 0 infers 8KB for compatibility.
+                    banks if !archaic => banks as usize * 8192,
+                    _ => 8192,
+                };
+
+                Ok(CartridgeHeader {
+                    format: Format::INes,
+                    mapper: (mapper_high | (raw[6] >> 4)) as u16,
+                    submapper: 0,
+                    prg_rom_size: raw[4] as usize * PRG_SIZE,
+                    chr_rom_size,
+                    prg_ram_size: if battery { 0 } else { prg_ram_size },
+                    prg_nvram_size: if battery { prg_ram_size } else { 0 },
+                    chr_ram_size: if chr_rom_size == 0 { CHR_SIZE } else { 0 },
+                    chr_nvram_size: 0,
+                    mirroring,
+                    battery,
+                    trainer,
+                    timing: if !archaic && raw[9] & 0b1 != 0 {
+                        Timing::Pal
+                    } else {
+                        Timing::Ntsc
+                    },
+                    console: match raw[7] & 0b11 {
+                        1 if !archaic => ConsoleType::VsSystem {
+                            ppu: 0,
+                            hardware: 0,
+                        },
+                        2 if !archaic => ConsoleType::Playchoice10,
+                        _ => ConsoleType::Nes,
+                    },
+                    misc_roms: 0,
+                    expansion_device: 0,
+                })
+            }
+        }
+    }
+
+    /// Whether the board carries writable CHR-RAM instead of CHR-ROM.
+    pub fn has_chr_ram(&self) -> bool {
+        self.chr_rom_size == 0
+    }
+
+    /// The size of the memory the PPU sees at $0000-$1FFF, either ROM or RAM.
+    pub fn chr_size(&self) -> usize {
+        if !self.has_chr_ram() {
+            self.chr_rom_size
+        } else if self.chr_ram_size + self.chr_nvram_size > 0 {
+            self.chr_ram_size + self.chr_nvram_size
+        } else {
+            // Some NES 2.0 dumps leave the RAM size blank, assume the usual 8KB.
+            CHR_SIZE
+        }
+    }
+
+    fn parse_nes2(
+        raw: &[u8],
+        mirroring: Mirroring,
+        battery: bool,
+        trainer: bool,
+    ) -> Result<CartridgeHeader, CartridgeError> {
+        let mapper =
+            (raw[8] as u16 & 0x0F) << 8 | (raw[7] & 0b1111_0000) as u16 | (raw[6] >> 4) as u16;
+
+        let prg_rom_size = rom_size(raw[4], raw[9] & 0x0F, PRG_SIZE)
+            .ok_or(CartridgeError::UnsupportedFormat("PRG-ROM size is too large"))?;
+        let chr_rom_size = rom_size(raw[5], raw[9] >> 4, CHR_SIZE)
+            .ok_or(CartridgeError::UnsupportedFormat("CHR-ROM size is too large"))?;
+
+        let console = match raw[7] & 0b11 {
+            0 => ConsoleType::Nes,
+            1 => ConsoleType::VsSystem {
+                ppu: raw[13] & 0x0F,
+                hardware: raw[13] >> 4,
+            },
+            2 => ConsoleType::Playchoice10,
+            _ => ConsoleType::Extended(raw[13] & 0x0F),
+        };
+
+        let timing = match raw[12] & 0b11 {
+            0 => Timing::Ntsc,
+            1 => Timing::Pal,
+            2 => Timing::MultiRegion,
+            _ => Timing::Dendy,
+        };
+
+        Ok(CartridgeHeader {
+            format: Format::Nes2,
+            mapper,
+            submapper: raw[8] >> 4,
+            prg_rom_size,
+            chr_rom_size,
+            prg_ram_size: ram_size(raw[10] & 0x0F),
+            prg_nvram_size: ram_size(raw[10] >> 4),
+            chr_ram_size: ram_size(raw[11] & 0x0F),
+            chr_nvram_size: ram_size(raw[11] >> 4),
+            mirroring,
+            battery,
+            trainer,
+            timing,
+            console,
+            misc_roms: raw[14] & 0b11,
+            expansion_device: raw[15] & 0b11_1111,
+        })
+    }
+}
+
+/// Decodes a NES 2.0 ROM size, either a plain count of `unit`s or, when the
+/// MSB nibble is $F, an exponent-multiplier pair `2^E * (MM * 2 + 1)`.
+fn rom_size(lsb: u8, msb: u8, unit: usize) -> Option<usize> {
+    if msb == 0x0F {
+        let exponent = (lsb >> 2) as u32;
+        let multiplier = (lsb & 0b11) as usize * 2 + 1;
+        1usize.checked_shl(exponent)?.checked_mul(multiplier)
+    } else {
+        ((msb as usize) << 8 | lsb as usize).checked_mul(unit)
+    }
+}
+
+/// Decodes a NES 2.0 RAM size given as a shift count: `64 << shift`, or none.
+fn ram_size(shift: u8) -> usize {
+    if shift == 0 {
+        0
+    } else {
+        64 << shift
+    }
+}
@@ -0,0 +1,179 @@
+use std::path::PathBuf;
+
+use crate::{
+    mapper::{self, Mapper, Mapping},
+    memory::{Address, Byte, Memory},
+    ppu::nametable::FOUR_SCREEN_VRAM_SIZE,
+};
+
+use self::header::{CartridgeHeader, HEADER_SIZE, TRAINER_SIZE};
+
+pub use self::error::CartridgeError;
+
+pub mod error;
+pub mod header;
+pub mod save;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Mirroring {
+    Horizontal,
+    Vertical,
+    FourScreen,
+    SingleScreenA,
+    SingleScreenB,
+}
+
+const PRG_RAM_SIZE: usize = 0x2000;
+const TRAINER_OFFSET: usize = 0x1000; // $7000 within the PRG-RAM at $6000.
+
+pub struct Cartridge {
+    pub header: CartridgeHeader,
+    pub prg: Vec<Byte>,
+    pub chr: Vec<Byte>,
+    pub prg_ram: Vec<Byte>,
+    pub trainer: Option<Vec<Byte>>,
+    /// Extra nametable RAM, only present on four-screen boards.
+    pub vram: Vec<Byte>,
+    pub mapper: Box<dyn Mapper>,
+    save_path: Option<PathBuf>,
+    save_dirty: bool,
+}
+
+impl Cartridge {
+    pub fn new(raw: &[u8]) -> Result<Cartridge, CartridgeError> {
+        let header = CartridgeHeader::parse(raw)?;
+
+        let mut data = &raw[HEADER_SIZE..];
+        let trainer = if header.trainer {
+            let trainer = take(&mut data, TRAINER_SIZE).map_err(|actual| CartridgeError::TruncatedTrainer {
+                expected: TRAINER_SIZE,
+                actual,
+            })?;
+            Some(trainer.to_vec())
+        } else {
+            None
+        };
+
+        let prg = take(&mut data, header.prg_rom_size).map_err(|actual| CartridgeError::TruncatedPrg {
+            expected: header.prg_rom_size,
+            actual,
+        })?;
+        let chr = take(&mut data, header.chr_rom_size).map_err(|actual| CartridgeError::TruncatedChr {
+            expected: header.chr_rom_size,
+            actual,
+        })?;
+
+        let mapper = mapper::new(&header).ok_or(CartridgeError::UnsupportedMapper {
+            mapper: header.mapper,
+            submapper: header.submapper,
+        })?;
+
+        let mut prg_ram = vec![0; header.prg_ram_size + header.prg_nvram_size];
+        if let Some(trainer) = &trainer {
+            // The trainer is loaded at $7000-$71FF, which needs the full 8KB.
+            if prg_ram.len() < PRG_RAM_SIZE {
+                prg_ram.resize(PRG_RAM_SIZE, 0);
+            }
+            prg_ram[TRAINER_OFFSET..TRAINER_OFFSET + TRAINER_SIZE].copy_from_slice(trainer);
+        }
+
+        Ok(Self {
+            prg: prg.to_vec(),
+            chr: if header.has_chr_ram() {
+                vec![0; header.chr_size()]
+            } else {
+                chr.to_vec()
+            },
+            prg_ram,
+            trainer,
+            vram: if header.mirroring == Mirroring::FourScreen {
+                vec![0; FOUR_SCREEN_VRAM_SIZE]
+            } else {
+                Vec::new()
+            },
+            mapper,
+            header,
+            save_path: None,
+            save_dirty: false,
+        })
+    }
+
+    /// The current nametable mirroring, which some mappers switch at runtime.
+    pub fn mirroring(&self) -> Mirroring {
+        self.mapper.mirroring().unwrap_or(self.header.mirroring)
+    }
+
+    pub fn irq(&self) -> bool {
+        self.mapper.irq()
+    }
+
+    /// Advances the mapper by one PPU dot.
+    pub fn ppu_tick(&mut self) {
+        self.mapper.ppu_tick();
+    }
+
+    pub fn read_chr(&mut self, address: Address) -> Byte {
+        let offset = self.mapper.ppu_read(address);
+        self.chr.get(offset).copied().unwrap_or(0)
+    }
+
+    pub fn write_chr(&mut self, address: Address, value: Byte) {
+        let offset = self.mapper.ppu_write(address);
+        if !self.header.has_chr_ram() {
+            return; // CHR-ROM is read-only.
+        }
+
+        if let Some(byte) = self.chr.get_mut(offset) {
+            *byte = value;
+        }
+    }
+
+    /// Boards that map nothing at $6000-$7FFF still show the trainer at
+    /// $7000-$71FF, where it would have been loaded.
+    fn trainer_byte(&self, address: Address) -> Option<Byte> {
+        let offset = (address as usize).checked_sub(0x6000 + TRAINER_OFFSET)?;
+        self.trainer.as_ref()?.get(offset).copied()
+    }
+}
+
+/// Splits `size` bytes off the front of `data`, or returns how many were left.
+fn take<'a>(data: &mut &'a [u8], size: usize) -> Result<&'a [u8], usize> {
+    if data.len() < size {
+        return Err(data.len());
+    }
+
+    let (head, tail) = data.split_at(size);
+    *data = tail;
+    Ok(head)
+}
+
+/// The cartridge sees the CPU addresses $4020-$FFFF unchanged.
+impl Memory for Cartridge {
+    fn read_byte(&mut self, address: Address) -> Byte {
+        match self.mapper.cpu_read(address) {
+            Mapping::Prg(offset) => self.prg.get(offset).copied().unwrap_or(0),
+            Mapping::Ram(offset) => match self.prg_ram.len() {
+                0 => 0x00,
+                len => self.prg_ram[offset % len],
+            },
+            Mapping::None => self.trainer_byte(address).unwrap_or(0x00),
+        }
+    }
+
+    fn write_byte(&mut self, address: Address, value: Byte) {
+        let mut value = value;
+        if self.mapper.bus_conflicts() {
+            if let Mapping::Prg(offset) = self.mapper.cpu_read(address) {
+                value &= self.prg.get(offset).copied().unwrap_or(0);
+            }
+        }
+
+        if let Mapping::Ram(offset) = self.mapper.cpu_write(address, value) {
+            let len = self.prg_ram.len();
+            if len > 0 {
+                self.prg_ram[offset % len] = value;
+                self.save_dirty = true;
+            }
+        }
+    }
+}
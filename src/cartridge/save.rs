@@ -0,0 +1,77 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use super::{Cartridge, CartridgeError};
+use crate::memory::Byte;
+
+impl Cartridge {
+    /// Whether the PRG-RAM is battery-backed and should outlive the session.
+    pub fn has_battery(&self) -> bool {
+        self.header.battery
+    }
+
+    /// Returns the PRG-RAM contents as a `.sav` blob.
+    pub fn export_save(&self) -> Vec<Byte> {
+        self.prg_ram.clone()
+    }
+
+    /// Restores the PRG-RAM from a `.sav` blob produced by `export_save`.
+    pub fn import_save(&mut self, data: &[Byte]) -> Result<(), CartridgeError> {
+        if data.len() != self.prg_ram.len() {
+            return Err(CartridgeError::SaveSizeMismatch {
+                expected: self.prg_ram.len(),
+                actual: data.len(),
+            });
+        }
+
+        self.prg_ram.copy_from_slice(data);
+        self.save_dirty = false;
+        Ok(())
+    }
+
+    /// Keeps the PRG-RAM in a `.sav` file next to the ROM, if the cartridge
+    /// has a battery. An existing save is loaded right away, and the file is
+    /// rewritten by `flush_save` and when the cartridge is dropped.
+    pub fn enable_autosave<P: AsRef<Path>>(&mut self, rom_path: P) -> io::Result<()> {
+        if !self.has_battery() {
+            return Ok(());
+        }
+
+        let path = save_path(rom_path.as_ref());
+        match fs::read(&path) {
+            Ok(data) => self
+                .import_save(&data)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            Err(error) => return Err(error),
+        }
+
+        self.save_path = Some(path);
+        Ok(())
+    }
+
+    /// Writes the PRG-RAM to the autosave file if it changed since the last write.
+    pub fn flush_save(&mut self) -> io::Result<()> {
+        if let (Some(path), true) = (&self.save_path, self.save_dirty) {
+            fs::write(path, &self.prg_ram)?;
+            self.save_dirty = false;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Cartridge {
+    fn drop(&mut self) {
+        if let Err(error) = self.flush_save() {
+            eprintln!("Failed to write save file: {}", error);
+        }
+    }
+}
+
+/// "games/zelda.nes" saves to "games/zelda.sav".
+pub fn save_path(rom_path: &Path) -> PathBuf {
+    rom_path.with_extension("sav")
+}
@@ -0,0 +1,67 @@
+use crate::memory::Byte;
+
+/// A standard controller. Writing 1 to $4016 holds the strobe, which keeps
+/// reloading the shift register with the current buttons. After it is
+/// cleared, each read shifts out one button, in the order of the bits below.
+pub struct Controller {
+    pub buttons: Byte,
+    shift: Byte,
+    strobe: bool,
+}
+
+impl Controller {
+    pub const A: u8 = 1 << 0;
+    pub const B: u8 = 1 << 1;
+    pub const SELECT: u8 = 1 << 2;
+    pub const START: u8 = 1 << 3;
+    pub const UP: u8 = 1 << 4;
+    pub const DOWN: u8 = 1 << 5;
+    pub const LEFT: u8 = 1 << 6;
+    pub const RIGHT: u8 = 1 << 7;
+
+    pub fn new() -> Self {
+        Self {
+            buttons: 0,
+            shift: 0,
+            strobe: false,
+        }
+    }
+
+    pub fn set_button(&mut self, button: u8, pressed: bool) {
+        if pressed {
+            self.buttons |= button;
+        } else {
+            self.buttons &= !button;
+        }
+    }
+
+    /// Replaces the state of all buttons at once, e.g. once per frame.
+    pub fn set_buttons(&mut self, buttons: Byte) {
+        self.buttons = buttons;
+    }
+
+    pub fn write(&mut self, value: Byte) {
+        self.strobe = value & 0x01 != 0;
+        if self.strobe {
+            self.shift = self.buttons;
+        }
+    }
+
+    /// Returns the next button in bit 0. Official controllers return 1
+    /// once all 8 buttons have been read.
+    pub fn read(&mut self) -> Byte {
+        if self.strobe {
+            return self.buttons & Self::A;
+        }
+
+        let bit = self.shift & 0x01;
+        self.shift = (self.shift >> 1) | 0x80;
+        bit
+    }
+}
+
+impl Default for Controller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,5 +1,8 @@
+use std::marker::PhantomData;
+
 use super::registers::Registers;
-use super::CPU;
+use super::{CpuVariant, CPU};
+use crate::bus::CpuBus;
 use crate::memory::addressing::Addressing;
 use crate::memory::addressing::Indirect;
 use crate::memory::addressing::ReadMode;
@@ -9,43 +12,70 @@ use crate::memory::addressing::{
     Absolute, AbsoluteX, AbsoluteY, Accumulator, Immediate, IndexedIndirectX, IndirectIndexedY,
     ZeroPage, ZeroPageX, ZeroPageY, Implied
 };
+use crate::memory::addressing::{AbsoluteIndexedIndirect, ZeroPageIndirect, ZeroPageRelative};
 use crate::memory::Byte;
-use crate::memory::DWord;
 use crate::memory::Memory;
 use crate::memory::Word;
 
-pub trait InstructionDefinition<M: Memory, A> {
+pub trait InstructionDefinition<B: CpuBus, A> {
     fn cycles() -> usize;
     fn page_boundary() -> bool {
         false
     }
 }
 
-pub trait ImpliedInstruction<M: Memory> {
-    fn execute(cpu: &mut CPU<M>);
+pub trait ImpliedInstruction<B: CpuBus> {
+    fn execute(cpu: &mut CPU<B>);
 }
 
-pub trait ReadInstruction<M: Memory> {
-    fn execute<RM: ReadMode<M>, ID: InstructionDefinition<M, RM>>(cpu: &mut CPU<M>)
+pub trait ReadInstruction<B: CpuBus> {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
     where
-        RM: Addressing<M>;
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>;
 }
 
-pub trait WriteInstruction<M: Memory> {
-    fn execute<WM: WriteMode<M>, ID: InstructionDefinition<M, WM>>(cpu: &mut CPU<M>)
+pub trait WriteInstruction<B: CpuBus> {
+    fn execute<WM, ID>(cpu: &mut CPU<B>)
     where
-        WM: Addressing<M>;
+        WM: WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, WM>;
 }
 
-pub trait ReadWriteInstruction<M: Memory> {
-    fn execute<RWM: ReadMode<M> + WriteMode<M>, ID: InstructionDefinition<M, RWM>>(cpu: &mut CPU<M>)
+pub trait ReadWriteInstruction<B: CpuBus> {
+    fn execute<RWM, ID>(cpu: &mut CPU<B>)
     where
-        RWM: Addressing<M>;
+        RWM: ReadMode<B> + WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RWM>;
 }
 
 macro_rules! instruction {
     ($instruction:ident, $mode:ident, $cycles:expr, $page_boundary:expr) => {
-        impl<M: Memory> InstructionDefinition<M, $mode> for $instruction {
+        impl<B: CpuBus> InstructionDefinition<B, $mode> for $instruction {
+            fn cycles() -> usize {
+                $cycles
+            }
+
+            fn page_boundary() -> bool {
+                $page_boundary
+            }
+        }
+    };
+    // NMOS instructions with the 65C02's timing, see `Cmos`.
+    (Cmos<$instruction:ident>, $mode:ident, $cycles:expr, $page_boundary:expr) => {
+        impl<B: CpuBus> InstructionDefinition<B, $mode> for Cmos<$instruction> {
+            fn cycles() -> usize {
+                $cycles
+            }
+
+            fn page_boundary() -> bool {
+                $page_boundary
+            }
+        }
+    };
+    // The 65C02 bit instructions, which take the bit number as a const parameter N.
+    ($instruction:ident<N>, $mode:ident, $cycles:expr, $page_boundary:expr) => {
+        impl<B: CpuBus, const N: u8> InstructionDefinition<B, $mode> for $instruction<N> {
             fn cycles() -> usize {
                 $cycles
             }
@@ -65,26 +95,17 @@ instruction!(ADC, AbsoluteX, 4, true);
 instruction!(ADC, AbsoluteY, 4, true);
 instruction!(ADC, IndexedIndirectX, 6, false);
 instruction!(ADC, IndirectIndexedY, 5, true);
+instruction!(ADC, ZeroPageIndirect, 5, false);
 pub struct ADC;
-impl<M: Memory> ReadInstruction<M> for ADC {
-    fn execute<RM: ReadMode<M>, ID: InstructionDefinition<M, RM>>(cpu: &mut CPU<M>)
+impl<B: CpuBus> ReadInstruction<B> for ADC {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
     where
-        RM: Addressing<M>,
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
     {
         let rm = RM::create_addressing(cpu, ID::page_boundary());
         let value = rm.read(cpu);
-        let result = cpu.registers.a as Word
-            + value as Word
-            + (cpu.registers.flags & Registers::CARRY_FLAG as Byte) as Word;
-
-        cpu.registers.set_flag(Registers::CARRY_FLAG, result > 0xFF);
-
-        let result = result as Byte;
-        cpu.registers.set_flag(
-            Registers::OVERFLOW_FLAG,
-            (value ^ result) & (result ^ cpu.registers.a) & 0x80 != 0,
-        );
-        cpu.registers.set_a(result);
+        cpu.add_with_carry(value);
     }
 }
 
@@ -96,11 +117,13 @@ instruction!(AND, AbsoluteX, 4, true);
 instruction!(AND, AbsoluteY, 4, true);
 instruction!(AND, IndexedIndirectX, 6, false);
 instruction!(AND, IndirectIndexedY, 5, true);
+instruction!(AND, ZeroPageIndirect, 5, false);
 pub struct AND;
-impl<M: Memory> ReadInstruction<M> for AND {
-    fn execute<RM: ReadMode<M>, ID: InstructionDefinition<M, RM>>(cpu: &mut CPU<M>)
+impl<B: CpuBus> ReadInstruction<B> for AND {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
     where
-        RM: Addressing<M>,
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
     {
         let rm = RM::create_addressing(cpu, ID::page_boundary());
         let value = rm.read(cpu) & cpu.registers.a;
@@ -112,12 +135,14 @@ instruction!(ASL, Accumulator, 2, false);
 instruction!(ASL, ZeroPage, 5, false);
 instruction!(ASL, ZeroPageX, 6, false);
 instruction!(ASL, Absolute, 6, false);
-instruction!(ASL, AbsoluteX, 7, true);
+instruction!(ASL, AbsoluteX, 7, false);
+instruction!(Cmos<ASL>, AbsoluteX, 6, true);
 pub struct ASL;
-impl<M: Memory> ReadWriteInstruction<M> for ASL {
-    fn execute<RWM: ReadMode<M> + WriteMode<M>, ID: InstructionDefinition<M, RWM>>(cpu: &mut CPU<M>)
+impl<B: CpuBus> ReadWriteInstruction<B> for ASL {
+    fn execute<RWM, ID>(cpu: &mut CPU<B>)
     where
-        RWM: Addressing<M>,
+        RWM: ReadMode<B> + WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RWM>,
     {
         cpu.shift_left::<RWM, ID>(false);
     }
@@ -125,40 +150,50 @@ impl<M: Memory> ReadWriteInstruction<M> for ASL {
 
 instruction!(BCC, Relative, 2, false);
 pub struct BCC;
-impl<M: Memory> ImpliedInstruction<M> for BCC {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for BCC {
+    fn execute(cpu: &mut CPU<B>) {
         cpu.branch(!cpu.registers.get_flag(Registers::CARRY_FLAG));
     }
 }
 
 instruction!(BCS, Relative, 2, false);
 pub struct BCS;
-impl<M: Memory> ImpliedInstruction<M> for BCS {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for BCS {
+    fn execute(cpu: &mut CPU<B>) {
         cpu.branch(cpu.registers.get_flag(Registers::CARRY_FLAG));
     }
 }
 
 instruction!(BEQ, Relative, 2, false);
 pub struct BEQ;
-impl<M: Memory> ImpliedInstruction<M> for BEQ {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for BEQ {
+    fn execute(cpu: &mut CPU<B>) {
         cpu.branch(cpu.registers.get_flag(Registers::ZERO_FLAG));
     }
 }
 
 instruction!(BIT, ZeroPage, 3, false);
 instruction!(BIT, Absolute, 4, false);
+instruction!(BIT, Immediate, 2, false);
+instruction!(BIT, ZeroPageX, 4, false);
+instruction!(BIT, AbsoluteX, 4, true);
 pub struct BIT;
-impl<M: Memory> ReadInstruction<M> for BIT {
-    fn execute<RM: ReadMode<M>, ID: InstructionDefinition<M, RM>>(cpu: &mut CPU<M>)
+impl<B: CpuBus> ReadInstruction<B> for BIT {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
     where
-        RM: Addressing<M>,
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
     {
         let rm = RM::create_addressing(cpu, ID::page_boundary());
         let value = rm.read(cpu);
         cpu.registers
             .set_flag(Registers::ZERO_FLAG, (value & cpu.registers.a) == 0);
+
+        // The 65C02's BIT #imm only affects Z.
+        if rm.get_address().is_none() {
+            return;
+        }
+
         cpu.registers
             .set_flag(Registers::NEGATIVE_FLAG, (value & 0x80) != 0);
         cpu.registers
@@ -168,74 +203,86 @@ impl<M: Memory> ReadInstruction<M> for BIT {
 
 instruction!(BMI, Relative, 2, false);
 pub struct BMI;
-impl<M: Memory> ImpliedInstruction<M> for BMI {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for BMI {
+    fn execute(cpu: &mut CPU<B>) {
         cpu.branch(cpu.registers.get_flag(Registers::NEGATIVE_FLAG));
     }
 }
 
 instruction!(BNE, Relative, 2, false);
 pub struct BNE;
-impl<M: Memory> ImpliedInstruction<M> for BNE {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for BNE {
+    fn execute(cpu: &mut CPU<B>) {
         cpu.branch(!cpu.registers.get_flag(Registers::ZERO_FLAG));
     }
 }
 
 instruction!(BPL, Relative, 2, false);
 pub struct BPL;
-impl<M: Memory> ImpliedInstruction<M> for BPL {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for BPL {
+    fn execute(cpu: &mut CPU<B>) {
         cpu.branch(!cpu.registers.get_flag(Registers::NEGATIVE_FLAG));
     }
 }
 
-// BRK
+instruction!(BRK, Implied, 7, false);
+pub struct BRK;
+impl<B: CpuBus> ImpliedInstruction<B> for BRK {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.dummy_read(cpu.registers.pc);
+        cpu.registers.pc = cpu.registers.pc.wrapping_add(1); // Skip the padding byte.
+        cpu.interrupt(Registers::IRQ_VECTOR, true);
+    }
+}
 
 instruction!(BVC, Relative, 2, false);
 pub struct BVC;
-impl<M: Memory> ImpliedInstruction<M> for BVC {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for BVC {
+    fn execute(cpu: &mut CPU<B>) {
         cpu.branch(!cpu.registers.get_flag(Registers::OVERFLOW_FLAG));
     }
 }
 
 instruction!(BVS, Relative, 2, false);
 pub struct BVS;
-impl<M: Memory> ImpliedInstruction<M> for BVS {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for BVS {
+    fn execute(cpu: &mut CPU<B>) {
         cpu.branch(cpu.registers.get_flag(Registers::OVERFLOW_FLAG));
     }
 }
 
 instruction!(CLC, Implied, 2, false);
 pub struct CLC;
-impl<M: Memory> ImpliedInstruction<M> for CLC {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for CLC {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
         cpu.registers.set_flag(Registers::CARRY_FLAG, false);
     }
 }
 
 instruction!(CLD, Implied, 2, false);
 pub struct CLD;
-impl<M: Memory> ImpliedInstruction<M> for CLD {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for CLD {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
         cpu.registers.set_flag(Registers::DECIMAL_FLAG, false);
     }
 }
 
 instruction!(CLI, Implied, 2, false);
 pub struct CLI;
-impl<M: Memory> ImpliedInstruction<M> for CLI {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for CLI {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
         cpu.registers.set_flag(Registers::IRQ_FLAG, false);
     }
 }
 
 instruction!(CLV, Implied, 2, false);
 pub struct CLV;
-impl<M: Memory> ImpliedInstruction<M> for CLV {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for CLV {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
         cpu.registers.set_flag(Registers::OVERFLOW_FLAG, false);
     }
 }
@@ -248,11 +295,13 @@ instruction!(CMP, AbsoluteX, 4, true);
 instruction!(CMP, AbsoluteY, 4, true);
 instruction!(CMP, IndexedIndirectX, 6, false);
 instruction!(CMP, IndirectIndexedY, 5, true);
+instruction!(CMP, ZeroPageIndirect, 5, false);
 pub struct CMP;
-impl<M: Memory> ReadInstruction<M> for CMP {
-    fn execute<RM: ReadMode<M>, ID: InstructionDefinition<M, RM>>(cpu: &mut CPU<M>)
+impl<B: CpuBus> ReadInstruction<B> for CMP {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
     where
-        RM: Addressing<M>,
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
     {
         cpu.compare::<RM, ID>(cpu.registers.a);
     }
@@ -262,10 +311,11 @@ instruction!(CPX, Immediate, 2, false);
 instruction!(CPX, ZeroPage, 3, false);
 instruction!(CPX, Absolute, 4, false);
 pub struct CPX;
-impl<M: Memory> ReadInstruction<M> for CPX {
-    fn execute<RM: ReadMode<M>, ID: InstructionDefinition<M, RM>>(cpu: &mut CPU<M>)
+impl<B: CpuBus> ReadInstruction<B> for CPX {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
     where
-        RM: Addressing<M>,
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
     {
         cpu.compare::<RM, ID>(cpu.registers.x);
     }
@@ -275,27 +325,31 @@ instruction!(CPY, Immediate, 2, false);
 instruction!(CPY, ZeroPage, 3, false);
 instruction!(CPY, Absolute, 4, false);
 pub struct CPY;
-impl<M: Memory> ReadInstruction<M> for CPY {
-    fn execute<RM: ReadMode<M>, ID: InstructionDefinition<M, RM>>(cpu: &mut CPU<M>)
+impl<B: CpuBus> ReadInstruction<B> for CPY {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
     where
-        RM: Addressing<M>,
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
     {
         cpu.compare::<RM, ID>(cpu.registers.y);
     }
 }
 
+instruction!(DEC, Accumulator, 2, false);
 instruction!(DEC, ZeroPage, 5, false);
 instruction!(DEC, ZeroPageX, 6, false);
 instruction!(DEC, Absolute, 6, false);
 instruction!(DEC, AbsoluteX, 7, false);
 pub struct DEC;
-impl<M: Memory> ReadWriteInstruction<M> for DEC {
-    fn execute<RWM: ReadMode<M> + WriteMode<M>, ID: InstructionDefinition<M, RWM>>(cpu: &mut CPU<M>)
+impl<B: CpuBus> ReadWriteInstruction<B> for DEC {
+    fn execute<RWM, ID>(cpu: &mut CPU<B>)
     where
-        RWM: Addressing<M>,
+        RWM: ReadMode<B> + WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RWM>,
     {
         let rwm = RWM::create_addressing(cpu, ID::page_boundary());
         let value = rwm.read(cpu);
+        rwm.dummy_write(cpu, value);
         let value = cpu.registers.set_zn(value.wrapping_sub(0x01));
         rwm.write(cpu, value);
     }
@@ -303,16 +357,18 @@ impl<M: Memory> ReadWriteInstruction<M> for DEC {
 
 instruction!(DEX, Implied, 2, false);
 pub struct DEX;
-impl<M: Memory> ImpliedInstruction<M> for DEX {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for DEX {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
         cpu.registers.set_x(cpu.registers.x.wrapping_sub(0x01));
     }
 }
 
 instruction!(DEY, Implied, 2, false);
 pub struct DEY;
-impl<M: Memory> ImpliedInstruction<M> for DEY {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for DEY {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
         cpu.registers.set_y(cpu.registers.y.wrapping_sub(0x01));
     }
 }
@@ -325,11 +381,13 @@ instruction!(EOR, AbsoluteX, 4, true);
 instruction!(EOR, AbsoluteY, 4, true);
 instruction!(EOR, IndexedIndirectX, 6, false);
 instruction!(EOR, IndirectIndexedY, 5, true);
+instruction!(EOR, ZeroPageIndirect, 5, false);
 pub struct EOR;
-impl<M: Memory> ReadInstruction<M> for EOR {
-    fn execute<RM: ReadMode<M>, ID: InstructionDefinition<M, RM>>(cpu: &mut CPU<M>)
+impl<B: CpuBus> ReadInstruction<B> for EOR {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
     where
-        RM: Addressing<M>,
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
     {
         let rm = RM::create_addressing(cpu, ID::page_boundary());
         let value = rm.read(cpu);
@@ -337,18 +395,21 @@ impl<M: Memory> ReadInstruction<M> for EOR {
     }
 }
 
+instruction!(INC, Accumulator, 2, false);
 instruction!(INC, ZeroPage, 5, false);
 instruction!(INC, ZeroPageX, 6, false);
 instruction!(INC, Absolute, 6, false);
-instruction!(INC, AbsoluteX, 7, true);
+instruction!(INC, AbsoluteX, 7, false);
 pub struct INC;
-impl<M: Memory> ReadWriteInstruction<M> for INC {
-    fn execute<RWM: ReadMode<M> + WriteMode<M>, ID: InstructionDefinition<M, RWM>>(cpu: &mut CPU<M>)
+impl<B: CpuBus> ReadWriteInstruction<B> for INC {
+    fn execute<RWM, ID>(cpu: &mut CPU<B>)
     where
-        RWM: Addressing<M>,
+        RWM: ReadMode<B> + WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RWM>,
     {
         let rwm = RWM::create_addressing(cpu, ID::page_boundary());
         let value = rwm.read(cpu);
+        rwm.dummy_write(cpu, value);
         let value = cpu.registers.set_zn(value.wrapping_add(0x01));
         rwm.write(cpu, value);
     }
@@ -356,36 +417,47 @@ impl<M: Memory> ReadWriteInstruction<M> for INC {
 
 instruction!(INX, Implied, 2, false);
 pub struct INX;
-impl<M: Memory> ImpliedInstruction<M> for INX {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for INX {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
         cpu.registers.set_x(cpu.registers.x.wrapping_add(0x01));
     }
 }
 
 instruction!(INY, Implied, 2, false);
 pub struct INY;
-impl<M: Memory> ImpliedInstruction<M> for INY {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for INY {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
         cpu.registers.set_y(cpu.registers.y.wrapping_add(0x01));
     }
 }
 
 instruction!(JMP, Absolute, 3, false);
 pub struct JMP;
-impl<M: Memory> ImpliedInstruction<M> for JMP {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for JMP {
+    fn execute(cpu: &mut CPU<B>) {
         cpu.registers.pc = cpu.read_next_word();
     }
 }
 
 instruction!(JMPI, Indirect, 5, false);
 pub struct JMPI;
-impl<M: Memory> ImpliedInstruction<M> for JMPI {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for JMPI {
+    fn execute(cpu: &mut CPU<B>) {
         let address = cpu.read_next_word();
 
+        // The pointer never crosses a page: ($10FF) reads its high byte from $1000.
+        // The 65C02 fixes that, at the cost of an extra cycle.
+        let high_address = if cpu.variant == CpuVariant::Cmos65C02 {
+            cpu.extra_cycle(cpu.registers.pc.wrapping_sub(1));
+            address.wrapping_add(0x0001)
+        } else {
+            (address & 0xff00) | (address.wrapping_add(0x0001) & 0x00ff)
+        };
+
         let low = cpu.read_byte(address);
-        let high = cpu.read_byte((address & 0xff00) | ((address + 0x0001) & 0x00ff));
+        let high = cpu.read_byte(high_address);
 
         cpu.registers.pc = (high as Word) << 8 | low as Word
     }
@@ -393,10 +465,14 @@ impl<M: Memory> ImpliedInstruction<M> for JMPI {
 
 instruction!(JSR, Absolute, 6, false);
 pub struct JSR;
-impl<M: Memory> ImpliedInstruction<M> for JSR {
-    fn execute(cpu: &mut CPU<M>) {
-        cpu.push_word(cpu.registers.pc + 0x0001);
-        cpu.registers.pc = cpu.read_word(cpu.registers.pc);
+impl<B: CpuBus> ImpliedInstruction<B> for JSR {
+    fn execute(cpu: &mut CPU<B>) {
+        // The high byte of the target is only fetched after pushing PC.
+        let low = cpu.read_next_byte() as Word;
+        cpu.stack_dummy_read();
+        cpu.push_word(cpu.registers.pc);
+        let high = cpu.read_byte(cpu.registers.pc) as Word;
+        cpu.registers.pc = high << 8 | low;
     }
 }
 
@@ -408,11 +484,13 @@ instruction!(LDA, AbsoluteX, 4, true);
 instruction!(LDA, AbsoluteY, 4, true);
 instruction!(LDA, IndexedIndirectX, 6, false);
 instruction!(LDA, IndirectIndexedY, 5, true);
+instruction!(LDA, ZeroPageIndirect, 5, false);
 pub struct LDA;
-impl<M: Memory> ReadInstruction<M> for LDA {
-    fn execute<RM: ReadMode<M>, ID: InstructionDefinition<M, RM>>(cpu: &mut CPU<M>)
+impl<B: CpuBus> ReadInstruction<B> for LDA {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
     where
-        RM: Addressing<M>,
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
     {
         let rm = RM::create_addressing(cpu, ID::page_boundary());
         let value: Byte = rm.read(cpu);
@@ -426,10 +504,11 @@ instruction!(LDX, ZeroPageY, 4, false);
 instruction!(LDX, Absolute, 4, false);
 instruction!(LDX, AbsoluteY, 4, true);
 pub struct LDX;
-impl<M: Memory> ReadInstruction<M> for LDX {
-    fn execute<RM: ReadMode<M>, ID: InstructionDefinition<M, RM>>(cpu: &mut CPU<M>)
+impl<B: CpuBus> ReadInstruction<B> for LDX {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
     where
-        RM: Addressing<M>,
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
     {
         let rm = RM::create_addressing(cpu, ID::page_boundary());
         let value: Byte = rm.read(cpu);
@@ -443,10 +522,11 @@ instruction!(LDY, ZeroPageX, 4, false);
 instruction!(LDY, Absolute, 4, false);
 instruction!(LDY, AbsoluteX, 4, true);
 pub struct LDY;
-impl<M: Memory> ReadInstruction<M> for LDY {
-    fn execute<RM: ReadMode<M>, ID: InstructionDefinition<M, RM>>(cpu: &mut CPU<M>)
+impl<B: CpuBus> ReadInstruction<B> for LDY {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
     where
-        RM: Addressing<M>,
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
     {
         let rm = RM::create_addressing(cpu, ID::page_boundary());
         let value: Byte = rm.read(cpu);
@@ -458,21 +538,42 @@ instruction!(LSR, Accumulator, 2, false);
 instruction!(LSR, ZeroPage, 5, false);
 instruction!(LSR, ZeroPageX, 6, false);
 instruction!(LSR, Absolute, 6, false);
-instruction!(LSR, AbsoluteX, 7, true);
+instruction!(LSR, AbsoluteX, 7, false);
+instruction!(Cmos<LSR>, AbsoluteX, 6, true);
 pub struct LSR;
-impl<M: Memory> ReadWriteInstruction<M> for LSR {
-    fn execute<RWM: ReadMode<M> + WriteMode<M>, ID:  InstructionDefinition<M, RWM>>(cpu: &mut CPU<M>)
+impl<B: CpuBus> ReadWriteInstruction<B> for LSR {
+    fn execute<RWM, ID>(cpu: &mut CPU<B>)
     where
-        RWM: Addressing<M>,
+        RWM: ReadMode<B> + WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RWM>,
     {
         cpu.shift_right::<RWM, ID>(false);
     }
 }
 
 instruction!(NOP, Implied, 2, false);
+instruction!(NOP, Immediate, 2, false);
+instruction!(NOP, ZeroPage, 3, false);
+instruction!(NOP, ZeroPageX, 4, false);
+instruction!(NOP, Absolute, 4, false);
+instruction!(NOP, AbsoluteX, 4, true);
 pub struct NOP;
-impl<M: Memory> ImpliedInstruction<M> for NOP {
-    fn execute(_cpu: &mut CPU<M>) {}
+impl<B: CpuBus> ImpliedInstruction<B> for NOP {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
+    }
+}
+
+// The unofficial DOP/TOP variants still perform their (dummy) read.
+impl<B: CpuBus> ReadInstruction<B> for NOP {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
+    where
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
+    {
+        let rm = RM::create_addressing(cpu, ID::page_boundary());
+        rm.read(cpu);
+    }
 }
 
 instruction!(ORA, Immediate, 2, false);
@@ -483,11 +584,13 @@ instruction!(ORA, AbsoluteX, 4, true);
 instruction!(ORA, AbsoluteY, 4, true);
 instruction!(ORA, IndexedIndirectX, 6, false);
 instruction!(ORA, IndirectIndexedY, 5, true);
+instruction!(ORA, ZeroPageIndirect, 5, false);
 pub struct ORA;
-impl<M: Memory> ReadInstruction<M> for ORA {
-    fn execute<RM: ReadMode<M>, ID: InstructionDefinition<M, RM>>(cpu: &mut CPU<M>)
+impl<B: CpuBus> ReadInstruction<B> for ORA {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
     where
-        RM: Addressing<M> + ,
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
     {
         let rm = RM::create_addressing(cpu, ID::page_boundary());
         let value: Byte = rm.read(cpu) | cpu.registers.a;
@@ -497,24 +600,28 @@ impl<M: Memory> ReadInstruction<M> for ORA {
 
 instruction!(PHA, Implied, 3, false);
 pub struct PHA;
-impl<M: Memory> ImpliedInstruction<M> for PHA {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for PHA {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
         cpu.push_byte(cpu.registers.a);
     }
 }
 
 instruction!(PHP, Implied, 3, false);
 pub struct PHP;
-impl<M: Memory> ImpliedInstruction<M> for PHP {
-    fn execute(cpu: &mut CPU<M>) {
-        cpu.push_byte(cpu.registers.flags | Registers::BREAK_FLAG);
+impl<B: CpuBus> ImpliedInstruction<B> for PHP {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
+        cpu.push_byte(cpu.registers.flags | Registers::BREAK_FLAG | Registers::UNUSED_FLAG);
     }
 }
 
 instruction!(PLA, Implied, 4, false);
 pub struct PLA;
-impl<M: Memory> ImpliedInstruction<M> for PLA {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for PLA {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
+        cpu.stack_dummy_read();
         let value = cpu.pop_byte();
         cpu.registers.set_a(value);
     }
@@ -522,8 +629,10 @@ impl<M: Memory> ImpliedInstruction<M> for PLA {
 
 instruction!(PLP, Implied, 4, false);
 pub struct PLP;
-impl<M: Memory> ImpliedInstruction<M> for PLP {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for PLP {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
+        cpu.stack_dummy_read();
         let flags = cpu.pop_byte();
         cpu.registers.set_flags(flags);
     }
@@ -533,12 +642,14 @@ instruction!(ROL, Accumulator, 2, false);
 instruction!(ROL, ZeroPage, 5, false);
 instruction!(ROL, ZeroPageX, 6, false);
 instruction!(ROL, Absolute, 6, false);
-instruction!(ROL, AbsoluteX, 7, true);
+instruction!(ROL, AbsoluteX, 7, false);
+instruction!(Cmos<ROL>, AbsoluteX, 6, true);
 pub struct ROL;
-impl<M: Memory> ReadWriteInstruction<M> for ROL {
-    fn execute<RWM: ReadMode<M> + WriteMode<M>, ID: InstructionDefinition<M, RWM>>(cpu: &mut CPU<M>)
+impl<B: CpuBus> ReadWriteInstruction<B> for ROL {
+    fn execute<RWM, ID>(cpu: &mut CPU<B>)
     where
-        RWM: Addressing<M>,
+        RWM: ReadMode<B> + WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RWM>,
     {
         cpu.shift_left::<RWM, ID>(cpu.registers.get_flag(Registers::CARRY_FLAG));
     }
@@ -548,12 +659,14 @@ instruction!(ROR, Accumulator, 2, false);
 instruction!(ROR, ZeroPage, 5, false);
 instruction!(ROR, ZeroPageX, 6, false);
 instruction!(ROR, Absolute, 6, false);
-instruction!(ROR, AbsoluteX, 7, true);
+instruction!(ROR, AbsoluteX, 7, false);
+instruction!(Cmos<ROR>, AbsoluteX, 6, true);
 pub struct ROR;
-impl<M: Memory> ReadWriteInstruction<M> for ROR {
-    fn execute<RWM: ReadMode<M> + WriteMode<M>, ID: InstructionDefinition<M, RWM>>(cpu: &mut CPU<M>)
+impl<B: CpuBus> ReadWriteInstruction<B> for ROR {
+    fn execute<RWM, ID>(cpu: &mut CPU<B>)
     where
-        RWM: Addressing<M>,
+        RWM: ReadMode<B> + WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RWM>,
     {
         cpu.shift_right::<RWM, ID>(cpu.registers.get_flag(Registers::CARRY_FLAG));
     }
@@ -561,8 +674,10 @@ impl<M: Memory> ReadWriteInstruction<M> for ROR {
 
 instruction!(RTI, Implied, 6, false);
 pub struct RTI;
-impl<M: Memory> ImpliedInstruction<M> for RTI {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for RTI {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
+        cpu.stack_dummy_read();
         let flags = cpu.pop_byte();
         cpu.registers.set_flags(flags);
         cpu.registers.pc = cpu.pop_word();
@@ -571,9 +686,14 @@ impl<M: Memory> ImpliedInstruction<M> for RTI {
 
 instruction!(RTS, Implied, 6, false);
 pub struct RTS;
-impl<M: Memory> ImpliedInstruction<M> for RTS {
-    fn execute(cpu: &mut CPU<M>) {
-        cpu.registers.pc = cpu.pop_word() + 0x0001;
+impl<B: CpuBus> ImpliedInstruction<B> for RTS {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
+        cpu.stack_dummy_read();
+        cpu.registers.pc = cpu.pop_word();
+        // PC is incremented in an extra cycle, past the last byte of the JSR.
+        cpu.dummy_read(cpu.registers.pc);
+        cpu.registers.pc = cpu.registers.pc.wrapping_add(0x0001);
     }
 }
 
@@ -585,51 +705,43 @@ instruction!(SBC, AbsoluteX, 4, true);
 instruction!(SBC, AbsoluteY, 4, true);
 instruction!(SBC, IndexedIndirectX, 6, false);
 instruction!(SBC, IndirectIndexedY, 5, true);
+instruction!(SBC, ZeroPageIndirect, 5, false);
 pub struct SBC;
-impl<M: Memory> ReadInstruction<M> for SBC {
-    fn execute<RM: ReadMode<M>, ID: InstructionDefinition<M, RM>>(cpu: &mut CPU<M>)
+impl<B: CpuBus> ReadInstruction<B> for SBC {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
     where
-        RM: Addressing<M>,
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
     {
         let rm = RM::create_addressing(cpu, ID::page_boundary());
         let value = rm.read(cpu);
-        let mut result = (cpu.registers.a as DWord).wrapping_sub(value as DWord);
-        if !cpu.registers.get_flag(Registers::CARRY_FLAG) {
-            result = result.wrapping_sub(0x0000_0001);
-        }
-
-        cpu.registers
-            .set_flag(Registers::CARRY_FLAG, (result & 0x0100) == 0);
-
-        let result = result as Byte;
-        cpu.registers.set_flag(
-            Registers::OVERFLOW_FLAG,
-            (cpu.registers.a ^ result) & 0x80 != 0 && (cpu.registers.a ^ value) & 0x80 == 0x80,
-        );
-        cpu.registers.set_a(result);
+        cpu.subtract_with_carry(value);
     }
 }
 
 instruction!(SEC, Implied, 2, false);
 pub struct SEC;
-impl<M: Memory> ImpliedInstruction<M> for SEC {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for SEC {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
         cpu.registers.set_flag(Registers::CARRY_FLAG, true);
     }
 }
 
 instruction!(SED, Implied, 2, false);
 pub struct SED;
-impl<M: Memory> ImpliedInstruction<M> for SED {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for SED {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
         cpu.registers.set_flag(Registers::DECIMAL_FLAG, true);
     }
 }
 
 instruction!(SEI, Implied, 2, false);
 pub struct SEI;
-impl<M: Memory> ImpliedInstruction<M> for SEI {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for SEI {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
         cpu.registers.set_flag(Registers::IRQ_FLAG, true);
     }
 }
@@ -641,11 +753,13 @@ instruction!(STA, AbsoluteX, 5, false);
 instruction!(STA, AbsoluteY, 5, false);
 instruction!(STA, IndexedIndirectX, 6, false);
 instruction!(STA, IndirectIndexedY, 6, false);
+instruction!(STA, ZeroPageIndirect, 5, false);
 pub struct STA;
-impl<M: Memory> WriteInstruction<M> for STA {
-    fn execute<WM: WriteMode<M>, ID: InstructionDefinition<M, WM>>(cpu: &mut CPU<M>)
+impl<B: CpuBus> WriteInstruction<B> for STA {
+    fn execute<WM, ID>(cpu: &mut CPU<B>)
     where
-        WM: Addressing<M>,
+        WM: WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, WM>,
     {
         let wm = WM::create_addressing(cpu, ID::page_boundary());
         wm.write(cpu, cpu.registers.a);
@@ -656,10 +770,11 @@ instruction!(STX, ZeroPage, 3, false);
 instruction!(STX, ZeroPageY, 4, false);
 instruction!(STX, Absolute, 4, false);
 pub struct STX;
-impl<M: Memory> WriteInstruction<M> for STX {
-    fn execute<WM: WriteMode<M>, ID: InstructionDefinition<M, WM>>(cpu: &mut CPU<M>)
+impl<B: CpuBus> WriteInstruction<B> for STX {
+    fn execute<WM, ID>(cpu: &mut CPU<B>)
     where
-        WM: Addressing<M>,
+        WM: WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, WM>,
     {
         let wm = WM::create_addressing(cpu, ID::page_boundary());
         wm.write(cpu, cpu.registers.x);
@@ -670,10 +785,11 @@ instruction!(STY, ZeroPage, 3, false);
 instruction!(STY, ZeroPageX, 4, false);
 instruction!(STY, Absolute, 4, false);
 pub struct STY;
-impl<M: Memory> WriteInstruction<M> for STY {
-    fn execute<WM: WriteMode<M>, ID: InstructionDefinition<M, WM>>(cpu: &mut CPU<M>)
+impl<B: CpuBus> WriteInstruction<B> for STY {
+    fn execute<WM, ID>(cpu: &mut CPU<B>)
     where
-        WM: Addressing<M> + ,
+        WM: WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, WM>,
     {
         let wm = WM::create_addressing(cpu, ID::page_boundary());
         wm.write(cpu, cpu.registers.y);
@@ -682,48 +798,619 @@ impl<M: Memory> WriteInstruction<M> for STY {
 
 instruction!(TAX, Implied, 2, false);
 pub struct TAX;
-impl<M: Memory> ImpliedInstruction<M> for TAX {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for TAX {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
         cpu.registers.set_x(cpu.registers.a);
     }
 }
 
 instruction!(TAY, Implied, 2, false);
 pub struct TAY;
-impl<M: Memory> ImpliedInstruction<M> for TAY {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for TAY {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
         cpu.registers.set_y(cpu.registers.a);
     }
 }
 
 instruction!(TSX, Implied, 2, false);
 pub struct TSX;
-impl<M: Memory> ImpliedInstruction<M> for TSX {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for TSX {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
         cpu.registers.set_x(cpu.registers.sp);
     }
 }
 
 instruction!(TXA, Implied, 2, false);
 pub struct TXA;
-impl<M: Memory> ImpliedInstruction<M> for TXA {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for TXA {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
         cpu.registers.set_a(cpu.registers.x);
     }
 }
 
 instruction!(TXS, Implied, 2, false);
 pub struct TXS;
-impl<M: Memory> ImpliedInstruction<M> for TXS {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for TXS {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
         cpu.registers.sp = cpu.registers.x;
     }
 }
 
 instruction!(TYA, Implied, 2, false);
 pub struct TYA;
-impl<M: Memory> ImpliedInstruction<M> for TYA {
-    fn execute(cpu: &mut CPU<M>) {
+impl<B: CpuBus> ImpliedInstruction<B> for TYA {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
         cpu.registers.set_a(cpu.registers.y);
     }
 }
+
+// UNOFFICIAL OPCODES
+
+instruction!(AHX, AbsoluteY, 5, false);
+instruction!(AHX, IndirectIndexedY, 6, false);
+pub struct AHX;
+impl<B: CpuBus> WriteInstruction<B> for AHX {
+    fn execute<WM, ID>(cpu: &mut CPU<B>)
+    where
+        WM: WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, WM>,
+    {
+        let wm = WM::create_addressing(cpu, ID::page_boundary());
+        let value = cpu.registers.a & cpu.registers.x;
+        cpu.store_high_and(wm.get_address().unwrap(), cpu.registers.y, value);
+    }
+}
+
+instruction!(ALR, Immediate, 2, false);
+pub struct ALR;
+impl<B: CpuBus> ReadInstruction<B> for ALR {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
+    where
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
+    {
+        let rm = RM::create_addressing(cpu, ID::page_boundary());
+        let value = rm.read(cpu) & cpu.registers.a;
+        cpu.registers
+            .set_flag(Registers::CARRY_FLAG, (value & 0x01) != 0);
+        cpu.registers.set_a(value >> 1);
+    }
+}
+
+instruction!(ANC, Immediate, 2, false);
+pub struct ANC;
+impl<B: CpuBus> ReadInstruction<B> for ANC {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
+    where
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
+    {
+        let rm = RM::create_addressing(cpu, ID::page_boundary());
+        let value = rm.read(cpu) & cpu.registers.a;
+        cpu.registers.set_a(value);
+        cpu.registers
+            .set_flag(Registers::CARRY_FLAG, (value & 0x80) != 0);
+    }
+}
+
+instruction!(ARR, Immediate, 2, false);
+pub struct ARR;
+impl<B: CpuBus> ReadInstruction<B> for ARR {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
+    where
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
+    {
+        let rm = RM::create_addressing(cpu, ID::page_boundary());
+        let value = rm.read(cpu) & cpu.registers.a;
+        let carry = (cpu.registers.flags & Registers::CARRY_FLAG) << 7;
+        let result = (value >> 1) | carry;
+        cpu.registers.set_a(result);
+        cpu.registers
+            .set_flag(Registers::CARRY_FLAG, (result & 0x40) != 0);
+        cpu.registers.set_flag(
+            Registers::OVERFLOW_FLAG,
+            ((result >> 6) ^ (result >> 5)) & 0x01 != 0,
+        );
+    }
+}
+
+instruction!(AXS, Immediate, 2, false);
+pub struct AXS;
+impl<B: CpuBus> ReadInstruction<B> for AXS {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
+    where
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
+    {
+        let rm = RM::create_addressing(cpu, ID::page_boundary());
+        let value = rm.read(cpu);
+        let x = cpu.registers.a & cpu.registers.x;
+        cpu.compare_values(x, value);
+        cpu.registers.x = x.wrapping_sub(value);
+    }
+}
+
+instruction!(DCP, ZeroPage, 5, false);
+instruction!(DCP, ZeroPageX, 6, false);
+instruction!(DCP, Absolute, 6, false);
+instruction!(DCP, AbsoluteX, 7, false);
+instruction!(DCP, AbsoluteY, 7, false);
+instruction!(DCP, IndexedIndirectX, 8, false);
+instruction!(DCP, IndirectIndexedY, 8, false);
+pub struct DCP;
+impl<B: CpuBus> ReadWriteInstruction<B> for DCP {
+    fn execute<RWM, ID>(cpu: &mut CPU<B>)
+    where
+        RWM: ReadMode<B> + WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RWM>,
+    {
+        let rwm = RWM::create_addressing(cpu, ID::page_boundary());
+        let value = rwm.read(cpu);
+        rwm.dummy_write(cpu, value);
+        let value = value.wrapping_sub(0x01);
+        rwm.write(cpu, value);
+        cpu.compare_values(cpu.registers.a, value);
+    }
+}
+
+instruction!(ISB, ZeroPage, 5, false);
+instruction!(ISB, ZeroPageX, 6, false);
+instruction!(ISB, Absolute, 6, false);
+instruction!(ISB, AbsoluteX, 7, false);
+instruction!(ISB, AbsoluteY, 7, false);
+instruction!(ISB, IndexedIndirectX, 8, false);
+instruction!(ISB, IndirectIndexedY, 8, false);
+pub struct ISB;
+impl<B: CpuBus> ReadWriteInstruction<B> for ISB {
+    fn execute<RWM, ID>(cpu: &mut CPU<B>)
+    where
+        RWM: ReadMode<B> + WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RWM>,
+    {
+        let rwm = RWM::create_addressing(cpu, ID::page_boundary());
+        let value = rwm.read(cpu);
+        rwm.dummy_write(cpu, value);
+        let value = value.wrapping_add(0x01);
+        rwm.write(cpu, value);
+        cpu.subtract_with_carry(value);
+    }
+}
+
+instruction!(KIL, Implied, 2, false);
+pub struct KIL;
+impl<B: CpuBus> ImpliedInstruction<B> for KIL {
+    fn execute(cpu: &mut CPU<B>) {
+        // The CPU locks up on the opcode until it is reset.
+        cpu.idle_read();
+        cpu.registers.pc = cpu.registers.pc.wrapping_sub(1);
+    }
+}
+
+instruction!(LAS, AbsoluteY, 4, true);
+pub struct LAS;
+impl<B: CpuBus> ReadInstruction<B> for LAS {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
+    where
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
+    {
+        let rm = RM::create_addressing(cpu, ID::page_boundary());
+        let value = rm.read(cpu) & cpu.registers.sp;
+        cpu.registers.sp = value;
+        cpu.registers.x = value;
+        cpu.registers.set_a(value);
+    }
+}
+
+instruction!(LAX, ZeroPage, 3, false);
+instruction!(LAX, ZeroPageY, 4, false);
+instruction!(LAX, Absolute, 4, false);
+instruction!(LAX, AbsoluteY, 4, true);
+instruction!(LAX, IndexedIndirectX, 6, false);
+instruction!(LAX, IndirectIndexedY, 5, true);
+pub struct LAX;
+impl<B: CpuBus> ReadInstruction<B> for LAX {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
+    where
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
+    {
+        let rm = RM::create_addressing(cpu, ID::page_boundary());
+        let value: Byte = rm.read(cpu);
+        cpu.registers.x = value;
+        cpu.registers.set_a(value);
+    }
+}
+
+instruction!(LXA, Immediate, 2, false);
+pub struct LXA;
+impl<B: CpuBus> ReadInstruction<B> for LXA {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
+    where
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
+    {
+        let rm = RM::create_addressing(cpu, ID::page_boundary());
+        // Unstable: the magic constant varies between chips, $EE is the common one.
+        let value = (cpu.registers.a | 0xEE) & rm.read(cpu);
+        cpu.registers.x = value;
+        cpu.registers.set_a(value);
+    }
+}
+
+instruction!(RLA, ZeroPage, 5, false);
+instruction!(RLA, ZeroPageX, 6, false);
+instruction!(RLA, Absolute, 6, false);
+instruction!(RLA, AbsoluteX, 7, false);
+instruction!(RLA, AbsoluteY, 7, false);
+instruction!(RLA, IndexedIndirectX, 8, false);
+instruction!(RLA, IndirectIndexedY, 8, false);
+pub struct RLA;
+impl<B: CpuBus> ReadWriteInstruction<B> for RLA {
+    fn execute<RWM, ID>(cpu: &mut CPU<B>)
+    where
+        RWM: ReadMode<B> + WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RWM>,
+    {
+        let value = cpu.shift_left::<RWM, ID>(cpu.registers.get_flag(Registers::CARRY_FLAG));
+        cpu.registers.set_a(value & cpu.registers.a);
+    }
+}
+
+instruction!(RRA, ZeroPage, 5, false);
+instruction!(RRA, ZeroPageX, 6, false);
+instruction!(RRA, Absolute, 6, false);
+instruction!(RRA, AbsoluteX, 7, false);
+instruction!(RRA, AbsoluteY, 7, false);
+instruction!(RRA, IndexedIndirectX, 8, false);
+instruction!(RRA, IndirectIndexedY, 8, false);
+pub struct RRA;
+impl<B: CpuBus> ReadWriteInstruction<B> for RRA {
+    fn execute<RWM, ID>(cpu: &mut CPU<B>)
+    where
+        RWM: ReadMode<B> + WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RWM>,
+    {
+        let value = cpu.shift_right::<RWM, ID>(cpu.registers.get_flag(Registers::CARRY_FLAG));
+        cpu.add_with_carry(value);
+    }
+}
+
+instruction!(SAX, ZeroPage, 3, false);
+instruction!(SAX, ZeroPageY, 4, false);
+instruction!(SAX, Absolute, 4, false);
+instruction!(SAX, IndexedIndirectX, 6, false);
+pub struct SAX;
+impl<B: CpuBus> WriteInstruction<B> for SAX {
+    fn execute<WM, ID>(cpu: &mut CPU<B>)
+    where
+        WM: WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, WM>,
+    {
+        let wm = WM::create_addressing(cpu, ID::page_boundary());
+        wm.write(cpu, cpu.registers.a & cpu.registers.x);
+    }
+}
+
+instruction!(SHX, AbsoluteY, 5, false);
+pub struct SHX;
+impl<B: CpuBus> WriteInstruction<B> for SHX {
+    fn execute<WM, ID>(cpu: &mut CPU<B>)
+    where
+        WM: WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, WM>,
+    {
+        let wm = WM::create_addressing(cpu, ID::page_boundary());
+        cpu.store_high_and(wm.get_address().unwrap(), cpu.registers.y, cpu.registers.x);
+    }
+}
+
+instruction!(SHY, AbsoluteX, 5, false);
+pub struct SHY;
+impl<B: CpuBus> WriteInstruction<B> for SHY {
+    fn execute<WM, ID>(cpu: &mut CPU<B>)
+    where
+        WM: WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, WM>,
+    {
+        let wm = WM::create_addressing(cpu, ID::page_boundary());
+        cpu.store_high_and(wm.get_address().unwrap(), cpu.registers.x, cpu.registers.y);
+    }
+}
+
+instruction!(SLO, ZeroPage, 5, false);
+instruction!(SLO, ZeroPageX, 6, false);
+instruction!(SLO, Absolute, 6, false);
+instruction!(SLO, AbsoluteX, 7, false);
+instruction!(SLO, AbsoluteY, 7, false);
+instruction!(SLO, IndexedIndirectX, 8, false);
+instruction!(SLO, IndirectIndexedY, 8, false);
+pub struct SLO;
+impl<B: CpuBus> ReadWriteInstruction<B> for SLO {
+    fn execute<RWM, ID>(cpu: &mut CPU<B>)
+    where
+        RWM: ReadMode<B> + WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RWM>,
+    {
+        let value = cpu.shift_left::<RWM, ID>(false);
+        cpu.registers.set_a(value | cpu.registers.a);
+    }
+}
+
+instruction!(SRE, ZeroPage, 5, false);
+instruction!(SRE, ZeroPageX, 6, false);
+instruction!(SRE, Absolute, 6, false);
+instruction!(SRE, AbsoluteX, 7, false);
+instruction!(SRE, AbsoluteY, 7, false);
+instruction!(SRE, IndexedIndirectX, 8, false);
+instruction!(SRE, IndirectIndexedY, 8, false);
+pub struct SRE;
+impl<B: CpuBus> ReadWriteInstruction<B> for SRE {
+    fn execute<RWM, ID>(cpu: &mut CPU<B>)
+    where
+        RWM: ReadMode<B> + WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RWM>,
+    {
+        let value = cpu.shift_right::<RWM, ID>(false);
+        cpu.registers.set_a(value ^ cpu.registers.a);
+    }
+}
+
+instruction!(TAS, AbsoluteY, 5, false);
+pub struct TAS;
+impl<B: CpuBus> WriteInstruction<B> for TAS {
+    fn execute<WM, ID>(cpu: &mut CPU<B>)
+    where
+        WM: WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, WM>,
+    {
+        let wm = WM::create_addressing(cpu, ID::page_boundary());
+        cpu.registers.sp = cpu.registers.a & cpu.registers.x;
+        cpu.store_high_and(wm.get_address().unwrap(), cpu.registers.y, cpu.registers.sp);
+    }
+}
+
+instruction!(XAA, Immediate, 2, false);
+pub struct XAA;
+impl<B: CpuBus> ReadInstruction<B> for XAA {
+    fn execute<RM, ID>(cpu: &mut CPU<B>)
+    where
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
+    {
+        let rm = RM::create_addressing(cpu, ID::page_boundary());
+        // Unstable: the magic constant varies between chips, $EE is the common one.
+        let value = (cpu.registers.a | 0xEE) & cpu.registers.x & rm.read(cpu);
+        cpu.registers.set_a(value);
+    }
+}
+
+// 65C02 OPCODES
+
+/// An NMOS instruction running with the 65C02's timing, e.g. abs,X shifts
+/// and rotates, which only spend the fix-up cycle on a page crossing.
+pub struct Cmos<I>(PhantomData<I>);
+impl<B: CpuBus, I: ReadWriteInstruction<B>> ReadWriteInstruction<B> for Cmos<I> {
+    fn execute<RWM, ID>(cpu: &mut CPU<B>)
+    where
+        RWM: ReadMode<B> + WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RWM>,
+    {
+        I::execute::<RWM, ID>(cpu);
+    }
+}
+
+instruction!(BBR<N>, ZeroPageRelative, 5, false);
+pub struct BBR<const N: u8>;
+impl<B: CpuBus, const N: u8> ImpliedInstruction<B> for BBR<N> {
+    fn execute(cpu: &mut CPU<B>) {
+        let address = cpu.read_next_byte() as Word;
+        let value = cpu.read_byte(address);
+        cpu.dummy_read(address);
+        cpu.branch(value & (1 << N) == 0);
+    }
+}
+
+instruction!(BBS<N>, ZeroPageRelative, 5, false);
+pub struct BBS<const N: u8>;
+impl<B: CpuBus, const N: u8> ImpliedInstruction<B> for BBS<N> {
+    fn execute(cpu: &mut CPU<B>) {
+        let address = cpu.read_next_byte() as Word;
+        let value = cpu.read_byte(address);
+        cpu.dummy_read(address);
+        cpu.branch(value & (1 << N) != 0);
+    }
+}
+
+instruction!(BRA, Relative, 2, false);
+pub struct BRA;
+impl<B: CpuBus> ImpliedInstruction<B> for BRA {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.branch(true);
+    }
+}
+
+instruction!(JMPX, AbsoluteIndexedIndirect, 6, false);
+pub struct JMPX;
+impl<B: CpuBus> ImpliedInstruction<B> for JMPX {
+    fn execute(cpu: &mut CPU<B>) {
+        let base = cpu.read_next_word();
+        cpu.dummy_read(cpu.registers.pc.wrapping_sub(1));
+        let address = base.wrapping_add(cpu.registers.x as Word);
+        cpu.registers.pc = cpu.read_word(address);
+    }
+}
+
+instruction!(NOP1, Implied, 1, false);
+/// The 65C02's single cycle NOPs, which don't even read the next byte.
+pub struct NOP1;
+impl<B: CpuBus> ImpliedInstruction<B> for NOP1 {
+    fn execute(_cpu: &mut CPU<B>) {}
+}
+
+instruction!(NOP5C, Absolute, 8, false);
+/// $5C on the 65C02, which takes an absolute operand and 8 cycles.
+pub struct NOP5C;
+impl<B: CpuBus> ImpliedInstruction<B> for NOP5C {
+    fn execute(cpu: &mut CPU<B>) {
+        let address = cpu.read_next_word();
+        for _ in 0..5 {
+            cpu.dummy_read(address);
+        }
+    }
+}
+
+instruction!(PHX, Implied, 3, false);
+pub struct PHX;
+impl<B: CpuBus> ImpliedInstruction<B> for PHX {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
+        cpu.push_byte(cpu.registers.x);
+    }
+}
+
+instruction!(PHY, Implied, 3, false);
+pub struct PHY;
+impl<B: CpuBus> ImpliedInstruction<B> for PHY {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
+        cpu.push_byte(cpu.registers.y);
+    }
+}
+
+instruction!(PLX, Implied, 4, false);
+pub struct PLX;
+impl<B: CpuBus> ImpliedInstruction<B> for PLX {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
+        cpu.stack_dummy_read();
+        let value = cpu.pop_byte();
+        cpu.registers.set_x(value);
+    }
+}
+
+instruction!(PLY, Implied, 4, false);
+pub struct PLY;
+impl<B: CpuBus> ImpliedInstruction<B> for PLY {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
+        cpu.stack_dummy_read();
+        let value = cpu.pop_byte();
+        cpu.registers.set_y(value);
+    }
+}
+
+instruction!(RMB<N>, ZeroPage, 5, false);
+pub struct RMB<const N: u8>;
+impl<B: CpuBus, const N: u8> ReadWriteInstruction<B> for RMB<N> {
+    fn execute<RWM, ID>(cpu: &mut CPU<B>)
+    where
+        RWM: ReadMode<B> + WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RWM>,
+    {
+        let rwm = RWM::create_addressing(cpu, ID::page_boundary());
+        let value = rwm.read(cpu);
+        rwm.dummy_write(cpu, value);
+        rwm.write(cpu, value & !(1 << N));
+    }
+}
+
+instruction!(SMB<N>, ZeroPage, 5, false);
+pub struct SMB<const N: u8>;
+impl<B: CpuBus, const N: u8> ReadWriteInstruction<B> for SMB<N> {
+    fn execute<RWM, ID>(cpu: &mut CPU<B>)
+    where
+        RWM: ReadMode<B> + WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RWM>,
+    {
+        let rwm = RWM::create_addressing(cpu, ID::page_boundary());
+        let value = rwm.read(cpu);
+        rwm.dummy_write(cpu, value);
+        rwm.write(cpu, value | (1 << N));
+    }
+}
+
+instruction!(STP, Implied, 3, false);
+pub struct STP;
+impl<B: CpuBus> ImpliedInstruction<B> for STP {
+    fn execute(cpu: &mut CPU<B>) {
+        // Like KIL, the CPU stops until it is reset.
+        cpu.idle_read();
+        cpu.idle_read();
+        cpu.registers.pc = cpu.registers.pc.wrapping_sub(1);
+    }
+}
+
+instruction!(STZ, ZeroPage, 3, false);
+instruction!(STZ, ZeroPageX, 4, false);
+instruction!(STZ, Absolute, 4, false);
+instruction!(STZ, AbsoluteX, 5, false);
+pub struct STZ;
+impl<B: CpuBus> WriteInstruction<B> for STZ {
+    fn execute<WM, ID>(cpu: &mut CPU<B>)
+    where
+        WM: WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, WM>,
+    {
+        let wm = WM::create_addressing(cpu, ID::page_boundary());
+        wm.write(cpu, 0x00);
+    }
+}
+
+instruction!(TRB, ZeroPage, 5, false);
+instruction!(TRB, Absolute, 6, false);
+pub struct TRB;
+impl<B: CpuBus> ReadWriteInstruction<B> for TRB {
+    fn execute<RWM, ID>(cpu: &mut CPU<B>)
+    where
+        RWM: ReadMode<B> + WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RWM>,
+    {
+        let rwm = RWM::create_addressing(cpu, ID::page_boundary());
+        let value = rwm.read(cpu);
+        rwm.dummy_write(cpu, value);
+        cpu.registers
+            .set_flag(Registers::ZERO_FLAG, (value & cpu.registers.a) == 0);
+        rwm.write(cpu, value & !cpu.registers.a);
+    }
+}
+
+instruction!(TSB, ZeroPage, 5, false);
+instruction!(TSB, Absolute, 6, false);
+pub struct TSB;
+impl<B: CpuBus> ReadWriteInstruction<B> for TSB {
+    fn execute<RWM, ID>(cpu: &mut CPU<B>)
+    where
+        RWM: ReadMode<B> + WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RWM>,
+    {
+        let rwm = RWM::create_addressing(cpu, ID::page_boundary());
+        let value = rwm.read(cpu);
+        rwm.dummy_write(cpu, value);
+        cpu.registers
+            .set_flag(Registers::ZERO_FLAG, (value & cpu.registers.a) == 0);
+        rwm.write(cpu, value | cpu.registers.a);
+    }
+}
+
+instruction!(WAI, Implied, 3, false);
+pub struct WAI;
+impl<B: CpuBus> ImpliedInstruction<B> for WAI {
+    fn execute(cpu: &mut CPU<B>) {
+        cpu.idle_read();
+        cpu.idle_read();
+
+        // Keeps waiting until an interrupt is signalled. A masked IRQ still
+        // ends the wait, but execution just continues after the WAI.
+        if !cpu.nmi_pending && !cpu.irq_line {
+            cpu.registers.pc = cpu.registers.pc.wrapping_sub(1);
+        }
+    }
+}
@@ -2,8 +2,8 @@ use crate::memory::addressing::Addressing;
 use crate::memory::addressing::ReadMode;
 use crate::memory::addressing::WriteMode;
 use crate::{
-    cartridge::Cartridge,
-    memory::{Address, Byte, DWord, Memory, Offset, Word, ZeroPageAddress, ZeroPageMemory},
+    bus::CpuBus,
+    memory::{Address, Byte, DWord, Memory, Offset, Word, ZeroPageMemory},
 };
 
 use self::instructions::InstructionDefinition;
@@ -12,56 +12,57 @@ use self::{
     registers::Registers,
 };
 
+pub use self::variant::CpuVariant;
+
 pub mod instructions;
 pub mod registers;
 pub mod step;
+pub mod variant;
 
-pub struct CPU<M: Memory> {
+pub struct CPU<B: CpuBus> {
     pub registers: Registers,
-    pub memory: M,
-    pub cartridge: Cartridge,
+    pub bus: B,
     pub cycles: usize,
+    pub nmi_pending: bool,
+    pub irq_line: bool,
+    pub tracing: bool,
+    /// Ticks the bus on every memory access instead of once per instruction,
+    /// and issues the dummy accesses real hardware does in between.
+    pub cycle_accurate: bool,
+    pub variant: CpuVariant,
 }
 
-impl<M: Memory> Memory for CPU<M> {
+/// Every access is one CPU cycle. In cycle accurate mode the rest of the
+/// system is clocked right before it, so it sees the access at the right time.
+impl<B: CpuBus> Memory for CPU<B> {
     fn read_byte(&mut self, address: Address) -> Byte {
-        match address {
-            0x0000..=0x1fff => self.memory.read_byte(address),
-            0x8000..=0xffff => self.cartridge.read_byte(address - 0x8000),
-            _ => {
-                println!("Ignoring memory access (read) at {:04X}", address);
-                0x000
-            }
+        if self.cycle_accurate {
+            self.clock();
         }
+        self.bus.read_byte(address)
     }
 
     fn write_byte(&mut self, address: Address, value: Byte) {
-        match address {
-            0x0000..=0x1fff => self.memory.write_byte(address, value),
-            0x8000..=0xffff => panic!("Cannot write on cartridge."),
-            _ => {
-                println!("Ignoring memory access (write) at {:04X}", address)
-            }
+        if self.cycle_accurate {
+            self.clock();
         }
+        self.bus.write_byte(address, value)
     }
 }
 
-impl<M: Memory> ZeroPageMemory for CPU<M> {
-    fn read_word_zero_page(&mut self, address: ZeroPageAddress) -> Word {
-        let low = self.read_byte(address as Word) as Word;
-        let high = self.read_byte((address as Word + 1) as Word) as Word;
+impl<B: CpuBus> ZeroPageMemory for CPU<B> {}
 
-        low | high << 8
-    }
-}
-
-impl<M: Memory> CPU<M> {
-    pub fn new(cartridge: Cartridge, memory: M) -> Self {
+impl<B: CpuBus> CPU<B> {
+    pub fn new(bus: B) -> Self {
         Self {
             registers: Registers::new(),
-            memory,
-            cartridge,
+            bus,
             cycles: 0,
+            nmi_pending: false,
+            irq_line: false,
+            tracing: false,
+            cycle_accurate: false,
+            variant: CpuVariant::default(),
         }
     }
 
@@ -71,14 +72,143 @@ impl<M: Memory> CPU<M> {
         self.registers.y = 0;
 
         self.registers.sp = Registers::STACK_RESET;
-        self.registers.pc = self.read_word(Registers::RESET_VECTOR);
+        self.registers.pc = self.bus.read_word(Registers::RESET_VECTOR);
 
         self.registers.flags = Registers::IRQ_FLAG | Registers::UNUSED_FLAG;
     }
 
+    /// Services a non-maskable interrupt.
+    pub fn nmi(&mut self) {
+        self.interrupt_entry();
+        self.interrupt(Registers::NMI_VECTOR, false);
+    }
+
+    /// Services a maskable interrupt, unless masked by the IRQ flag.
+    pub fn irq(&mut self) {
+        if self.registers.get_flag(Registers::IRQ_FLAG) {
+            return;
+        }
+
+        self.interrupt_entry();
+        self.interrupt(Registers::IRQ_VECTOR, false);
+    }
+
+    /// Hardware interrupts take 7 cycles: two reads of the next opcode, which
+    /// is discarded, then the same 5 accesses as BRK.
+    fn interrupt_entry(&mut self) {
+        if self.cycle_accurate {
+            self.dummy_read(self.registers.pc);
+            self.dummy_read(self.registers.pc);
+        } else {
+            self.cycles += 7;
+        }
+    }
+
+    /// Pushes PC and the flags, then jumps through the given vector.
+    /// The B flag is only set in the pushed copy when coming from BRK.
+    pub fn interrupt(&mut self, vector: Address, brk: bool) {
+        self.push_word(self.registers.pc);
+
+        let mut flags = (self.registers.flags | Registers::UNUSED_FLAG) & !Registers::BREAK_FLAG;
+        if brk {
+            flags |= Registers::BREAK_FLAG;
+        }
+        self.push_byte(flags);
+
+        self.registers.set_flag(Registers::IRQ_FLAG, true);
+        if self.variant == CpuVariant::Cmos65C02 {
+            self.registers.set_flag(Registers::DECIMAL_FLAG, false);
+        }
+        self.registers.pc = self.read_word(vector);
+    }
+
+    /// Services the pending NMI or the asserted IRQ line, if any.
+    /// Returns `true` if an interrupt was taken instead of an instruction.
+    pub fn poll_interrupts(&mut self) -> bool {
+        if self.nmi_pending {
+            self.nmi_pending = false;
+            self.nmi();
+            true
+        } else if self.irq_line && !self.registers.get_flag(Registers::IRQ_FLAG) {
+            self.irq();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Runs a single CPU cycle, in cycle accurate mode.
+    fn clock(&mut self) {
+        self.cycles += 1;
+        self.tick(1);
+    }
+
+    /// A read whose value is thrown away, which only matters (and only takes
+    /// time) in cycle accurate mode. Reads can have side effects on I/O registers.
+    pub fn dummy_read(&mut self, address: Address) {
+        if self.cycle_accurate {
+            self.read_byte(address);
+        }
+    }
+
+    /// Like `dummy_read`, e.g. for the unmodified value RMW instructions write back.
+    pub fn dummy_write(&mut self, address: Address, value: Byte) {
+        if self.cycle_accurate {
+            self.write_byte(address, value);
+        }
+    }
+
+    /// A cycle spent on top of the instruction's base count, which is a
+    /// dummy read of `address` in cycle accurate mode.
+    pub fn extra_cycle(&mut self, address: Address) {
+        if self.cycle_accurate {
+            self.dummy_read(address);
+        } else {
+            self.cycles += 1;
+        }
+    }
+
+    /// Single byte instructions read the byte after the opcode and ignore it.
+    pub fn idle_read(&mut self) {
+        self.dummy_read(self.registers.pc);
+    }
+
+    /// The internal cycle of pulls (and JSR), which reads the top of the stack.
+    pub fn stack_dummy_read(&mut self) {
+        self.dummy_read(Registers::STACK + self.registers.sp as Word);
+    }
+
+    /// Accounts for an indexed address crossing a page: the CPU first reads
+    /// from the address without the carry into the high byte, then fixes it.
+    /// Reads can skip that cycle when no page is crossed, writes never do.
+    /// On a page crossing the 65C02 re-reads the last operand byte instead.
+    pub fn index_penalty(&mut self, base: Address, address: Address, page_boundary: bool) {
+        let crossed = base & 0xFF00 != address & 0xFF00;
+        if !self.cycle_accurate {
+            if page_boundary && crossed {
+                self.cycles += 1;
+            }
+        } else if crossed && self.variant == CpuVariant::Cmos65C02 {
+            self.dummy_read(self.registers.pc.wrapping_sub(1));
+        } else if crossed || !page_boundary {
+            self.dummy_read((base & 0xFF00) | (address & 0x00FF));
+        }
+    }
+
+    /// Advances the rest of the system by the given amount of CPU cycles.
+    pub fn tick(&mut self, cycles: usize) {
+        self.cycles += self.bus.tick(cycles);
+
+        if self.bus.poll_nmi() {
+            self.nmi_pending = true;
+        }
+        self.irq_line = self.bus.irq();
+    }
+
+    /// The stack pointer wraps around within page $01.
     pub fn push_byte(&mut self, value: u8) {
         self.write_byte(Registers::STACK + self.registers.sp as Word, value);
-        self.registers.sp -= 0x0001;
+        self.registers.sp = self.registers.sp.wrapping_sub(1);
     }
 
     pub fn push_word(&mut self, value: Word) {
@@ -89,7 +219,7 @@ impl<M: Memory> CPU<M> {
     }
 
     pub fn pop_byte(&mut self) -> Byte {
-        self.registers.sp += 1;
+        self.registers.sp = self.registers.sp.wrapping_add(1);
         self.read_byte(Registers::STACK + self.registers.sp as Word)
     }
 
@@ -101,40 +231,49 @@ impl<M: Memory> CPU<M> {
 
     pub fn read_next_byte(&mut self) -> Byte {
         let value = self.read_byte(self.registers.pc);
-        self.registers.pc += 1;
+        self.registers.pc = self.registers.pc.wrapping_add(1);
 
         value
     }
 
     pub fn read_next_word(&mut self) -> Word {
         let value = self.read_word(self.registers.pc);
-        self.registers.pc += 2;
+        self.registers.pc = self.registers.pc.wrapping_add(2);
 
         value
     }
 }
 
-impl<M: Memory> CPU<M> {
+impl<B: CpuBus> CPU<B> {
     pub fn branch(&mut self, condition: bool) {
         let offset = self.read_next_byte() as Offset;
         let current = self.registers.pc & 0xFF00;
 
         if condition {
-            self.cycles += 1; // +1 if branch succeeds.
-            self.registers.pc = (self.registers.pc as DWord + offset as DWord) as Word;
-            if self.registers.pc & 0xFF00 != current { 
-                self.cycles += 1; // +1 if to a new page
+            // +1 if branch succeeds, reading the next opcode meanwhile.
+            self.extra_cycle(self.registers.pc);
+
+            let target = self.registers.pc.wrapping_add(offset as Word);
+            if target & 0xFF00 != current {
+                // +1 if to a new page
+                self.extra_cycle(current | (target & 0x00FF));
             }
+            self.registers.pc = target;
         }
     }
 
-    pub fn compare<RM: ReadMode<M>, ID: InstructionDefinition<M, RM>>(&mut self, x: u8)
+    pub fn compare<RM, ID>(&mut self, x: u8)
     where
-        RM: Addressing<M>,
+        RM: ReadMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RM>,
     {
         let rm = RM::create_addressing(self, ID::page_boundary());
-        let y = rm.read(self) as DWord;
-        let result = (x as DWord).wrapping_sub(y);
+        let y = rm.read(self);
+        self.compare_values(x, y);
+    }
+
+    pub fn compare_values(&mut self, x: Byte, y: Byte) {
+        let result = (x as DWord).wrapping_sub(y as DWord);
         self.registers
             .set_flag(Registers::CARRY_FLAG, (result & 0x0100) == 0);
         self.registers.set_flag(Registers::ZERO_FLAG, result == 0);
@@ -142,12 +281,153 @@ impl<M: Memory> CPU<M> {
             .set_flag(Registers::NEGATIVE_FLAG, (result & 0x80) != 0);
     }
 
-    pub fn shift_left<RWM: ReadMode<M> + WriteMode<M>, ID: InstructionDefinition<M, RWM>>(&mut self, condition: bool)
+    /// Whether ADC and SBC currently work on packed BCD.
+    pub fn decimal_mode(&self) -> bool {
+        self.variant.has_decimal_mode() && self.registers.get_flag(Registers::DECIMAL_FLAG)
+    }
+
+    /// The 65C02 spends a cycle fixing up N and Z after a decimal ADC or SBC.
+    fn decimal_penalty(&mut self) {
+        if self.decimal_mode() && self.variant == CpuVariant::Cmos65C02 {
+            self.extra_cycle(self.registers.pc.wrapping_sub(1));
+        }
+    }
+
+    pub fn add_with_carry(&mut self, value: Byte) {
+        if self.decimal_mode() {
+            self.decimal_penalty();
+            self.add_decimal(value);
+            return;
+        }
+
+        let result = self.registers.a as Word
+            + value as Word
+            + (self.registers.flags & Registers::CARRY_FLAG as Byte) as Word;
+
+        self.registers.set_flag(Registers::CARRY_FLAG, result > 0xFF);
+
+        let result = result as Byte;
+        self.registers.set_flag(
+            Registers::OVERFLOW_FLAG,
+            (value ^ result) & (result ^ self.registers.a) & 0x80 != 0,
+        );
+        self.registers.set_a(result);
+    }
+
+    pub fn subtract_with_carry(&mut self, value: Byte) {
+        let decimal = self.decimal_mode().then(|| self.subtract_decimal(value));
+        self.decimal_penalty();
+
+        let mut result = (self.registers.a as DWord).wrapping_sub(value as DWord);
+        if !self.registers.get_flag(Registers::CARRY_FLAG) {
+            result = result.wrapping_sub(0x0000_0001);
+        }
+
+        self.registers
+            .set_flag(Registers::CARRY_FLAG, (result & 0x0100) == 0);
+
+        let result = result as Byte;
+        self.registers.set_flag(
+            Registers::OVERFLOW_FLAG,
+            (self.registers.a ^ result) & 0x80 != 0 && (self.registers.a ^ value) & 0x80 == 0x80,
+        );
+
+        // C and V always come from the binary difference, which the NMOS 6502
+        // also uses for N and Z.
+        match decimal {
+            Some(decimal) if self.variant == CpuVariant::Cmos65C02 => self.registers.set_a(decimal),
+            Some(decimal) => {
+                self.registers.set_zn(result);
+                self.registers.a = decimal;
+            }
+            None => self.registers.set_a(result),
+        }
+    }
+
+    /// Decimal ADC, as described in Bruce Clark's "Decimal Mode" tutorial.
+    /// The NMOS 6502 takes N and V from the sum before the high digit is
+    /// adjusted, and Z from the binary sum. The 65C02 fixes N and Z.
+    fn add_decimal(&mut self, value: Byte) {
+        let a = self.registers.a;
+        let carry = self.registers.get_flag(Registers::CARRY_FLAG) as Word;
+
+        let mut low = (a & 0x0F) as Word + (value & 0x0F) as Word + carry;
+        if low >= 0x0A {
+            low = ((low + 0x06) & 0x0F) + 0x10;
+        }
+
+        let sum = (a & 0xF0) as Word + (value & 0xF0) as Word + low;
+        let signed = (a & 0xF0) as i8 as i16 + (value & 0xF0) as i8 as i16 + low as i16;
+        self.registers
+            .set_flag(Registers::OVERFLOW_FLAG, !(-128..=127).contains(&signed));
+
+        let result = if sum >= 0xA0 { sum + 0x60 } else { sum };
+        self.registers.set_flag(Registers::CARRY_FLAG, result > 0xFF);
+
+        if self.variant == CpuVariant::Cmos65C02 {
+            self.registers.set_a(result as Byte);
+        } else {
+            let binary = (a as Word + value as Word + carry) as Byte;
+            self.registers.set_flag(Registers::ZERO_FLAG, binary == 0);
+            self.registers.set_flag(Registers::NEGATIVE_FLAG, sum & 0x80 != 0);
+            self.registers.a = result as Byte;
+        }
+    }
+
+    /// The accumulator result of a decimal SBC.
+    fn subtract_decimal(&self, value: Byte) -> Byte {
+        let a = self.registers.a as i16;
+        let value = value as i16;
+        let borrow = !self.registers.get_flag(Registers::CARRY_FLAG) as i16;
+
+        let mut low = (a & 0x0F) - (value & 0x0F) - borrow;
+        let result = if self.variant == CpuVariant::Cmos65C02 {
+            let mut result = a - value - borrow;
+            if result < 0 {
+                result -= 0x60;
+            }
+            if low < 0 {
+                result -= 0x06;
+            }
+            result
+        } else {
+            if low < 0 {
+                low = ((low - 0x06) & 0x0F) - 0x10;
+            }
+            let result = (a & 0xF0) - (value & 0xF0) + low;
+            if result < 0 {
+                result - 0x60
+            } else {
+                result
+            }
+        };
+
+        result as Byte
+    }
+
+    /// Stores `value & (H + 1)`, H being the high byte of the unindexed address,
+    /// as done by SHX/SHY/AHX/TAS. On a page crossing the stored value also
+    /// replaces the high byte of the target address.
+    pub fn store_high_and(&mut self, address: Address, index: Byte, value: Byte) {
+        let base = address.wrapping_sub(index as Word);
+        let value = value & ((base >> 8) as Byte).wrapping_add(1);
+        let address = if base & 0xFF00 != address & 0xFF00 {
+            (value as Word) << 8 | (address & 0x00FF)
+        } else {
+            address
+        };
+
+        self.write_byte(address, value);
+    }
+
+    pub fn shift_left<RWM, ID>(&mut self, condition: bool) -> Byte
     where
-        RWM: Addressing<M>,
+        RWM: ReadMode<B> + WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RWM>,
     {
         let rwm = RWM::create_addressing(self, ID::page_boundary());
         let value = rwm.read(self);
+        rwm.dummy_write(self, value);
         let mut result = value << 0x01;
         if condition {
             result |= 0x01;
@@ -157,14 +437,17 @@ impl<M: Memory> CPU<M> {
             .set_flag(Registers::CARRY_FLAG, (value & 0x80) != 0);
         self.registers.set_zn(result);
         rwm.write(self, result as Byte);
+        result
     }
 
-    pub fn shift_right<RWM: ReadMode<M> + WriteMode<M>, ID: InstructionDefinition<M, RWM>>(&mut self, condition: bool)
+    pub fn shift_right<RWM, ID>(&mut self, condition: bool) -> Byte
     where
-        RWM: Addressing<M>,
+        RWM: ReadMode<B> + WriteMode<B> + Addressing<B>,
+        ID: InstructionDefinition<B, RWM>,
     {
         let rwm = RWM::create_addressing(self, ID::page_boundary());
         let value = rwm.read(self);
+        rwm.dummy_write(self, value);
         let mut result = value >> 0x01;
         if condition {
             result |= 0x80;
@@ -174,37 +457,48 @@ impl<M: Memory> CPU<M> {
             .set_flag(Registers::CARRY_FLAG, (value & 0x01) != 0);
         self.registers.set_zn(result);
         rwm.write(self, result);
+        result
     }
 }
 
-impl<M: Memory> CPU<M> {
-    pub fn execute<II: ImpliedInstruction<M> + InstructionDefinition<M, S>, S>(&mut self) {
-        self.cycles += II::cycles();
+impl<B: CpuBus> CPU<B> {
+    pub fn execute<II: ImpliedInstruction<B> + InstructionDefinition<B, S>, S>(&mut self) {
+        if !self.cycle_accurate {
+            self.cycles += II::cycles();
+        }
         II::execute(self);
     }
 
-    pub fn execute_read<RI: ReadInstruction<M> + InstructionDefinition<M, RM>, RM: ReadMode<M>>(&mut self)
+    pub fn execute_read<RI, RM>(&mut self)
     where
-        RM: Addressing<M>,
+        RI: ReadInstruction<B> + InstructionDefinition<B, RM>,
+        RM: ReadMode<B> + Addressing<B>,
     {
-        self.cycles += RI::cycles();
+        if !self.cycle_accurate {
+            self.cycles += RI::cycles();
+        }
         RI::execute::<RM, RI>(self);
     }
 
-    pub fn execute_write<WI: WriteInstruction<M> + InstructionDefinition<M, WM>, WM: WriteMode<M>>(&mut self)
+    pub fn execute_write<WI, WM>(&mut self)
     where
-        WM: Addressing<M>,
+        WI: WriteInstruction<B> + InstructionDefinition<B, WM>,
+        WM: WriteMode<B> + Addressing<B>,
     {
-        self.cycles += WI::cycles();
+        if !self.cycle_accurate {
+            self.cycles += WI::cycles();
+        }
         WI::execute::<WM, WI>(self);
     }
 
-    pub fn execute_read_write<RWI: ReadWriteInstruction<M> + InstructionDefinition<M, RWM>, RWM: ReadMode<M> + WriteMode<M>>(
-        &mut self,
-    ) where
-        RWM: Addressing<M>,
+    pub fn execute_read_write<RWI, RWM>(&mut self)
+    where
+        RWI: ReadWriteInstruction<B> + InstructionDefinition<B, RWM>,
+        RWM: ReadMode<B> + WriteMode<B> + Addressing<B>,
     {
-        self.cycles += RWI::cycles();
+        if !self.cycle_accurate {
+            self.cycles += RWI::cycles();
+        }
         RWI::execute::<RWM, RWI>(self);
     }
 }
@@ -21,7 +21,9 @@ impl Registers {
 
     pub const STACK: u16 = 0x0100;
     pub const STACK_RESET: u8 = 0xfd;
+    pub const NMI_VECTOR: u16 = 0xFFFA;
     pub const RESET_VECTOR: u16 = 0xFFFC;
+    pub const IRQ_VECTOR: u16 = 0xFFFE;
 
     pub fn new() -> Self {
         Self {
@@ -69,3 +71,9 @@ impl Registers {
         self.x = self.set_zn(value);
     }
 }
+
+impl Default for Registers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,37 +1,76 @@
-use crate::memory::{Memory, addressing::{Relative, Implied, Indirect}};
+use crate::{bus::CpuBus, memory::{Byte, addressing::{Relative, Implied, Indirect}}};
 
 use super::{
     instructions::{
-        ADC, AND, ASL, BCC, BCS, BEQ, BIT, BMI, BNE, BPL, BVC, BVS, CLC, CLD, CLI, CLV, CMP, CPX,
+        ADC, AND, ASL, BCC, BCS, BEQ, BIT, BMI, BNE, BPL, BRK, BVC, BVS, CLC, CLD, CLI, CLV, CMP, CPX,
         CPY, DEC, DEX, DEY, EOR, INC, INX, INY, JMP, JMPI, JSR, LDA, LDX, LDY, LSR, NOP, ORA, PHA,
         PHP, PLA, PLP, ROL, ROR, RTI, RTS, SBC, SEC, SED, SEI, STA, STX, STY, TAX, TAY, TSX, TXA,
         TXS, TYA,
+        AHX, ALR, ANC, ARR, AXS, DCP, ISB, KIL, LAS, LAX, LXA, RLA, RRA, SAX, SHX, SHY, SLO, SRE,
+        TAS, XAA,
+        BBR, BBS, BRA, Cmos, JMPX, NOP1, NOP5C, PHX, PHY, PLX, PLY, RMB, SMB, STP, STZ, TRB, TSB, WAI,
     },
-    CPU,
+    CpuVariant, CPU,
 };
 use crate::memory::addressing::{
     Absolute, AbsoluteX, AbsoluteY, Accumulator, Immediate, IndexedIndirectX, IndirectIndexedY,
     ZeroPage, ZeroPageX, ZeroPageY,
 };
+use crate::memory::addressing::{AbsoluteIndexedIndirect, ZeroPageIndirect, ZeroPageRelative};
 
-impl<M: Memory> CPU<M> {
-    pub fn trace(&mut self) {
-        println!(
+impl<B: CpuBus> CPU<B> {
+    /// Formats the state of the CPU before the next instruction, in the format
+    /// used by `res/result.txt`.
+    pub fn trace_line(&mut self) -> String {
+        format!(
             "{:04X} OP:{:02X} A:{:02X} X:{:02X} Y:{:02X} FLAGS:{:02X} SP:{:02X} CYC:{}",
             self.registers.pc as usize,
-            self.read_byte(self.registers.pc) as usize,
+            self.bus.read_byte(self.registers.pc) as usize,
             self.registers.a as usize,
             self.registers.x as usize,
             self.registers.y as usize,
             self.registers.flags as usize,
             self.registers.sp as usize,
-            self.cycles as usize,
-        );
+            self.cycles,
+        )
+    }
+
+    pub fn trace(&mut self) {
+        if self.tracing {
+            println!("{}", self.trace_line());
+        }
+    }
+
+    /// In the default mode the rest of the system runs after each instruction,
+    /// for all of its cycles at once. In cycle accurate mode it already has.
+    fn catch_up(&mut self, cycles: usize) {
+        if !self.cycle_accurate {
+            self.tick(self.cycles - cycles);
+        }
     }
 
     pub fn step(&mut self) -> bool {
+        let cycles = self.cycles;
+        if self.poll_interrupts() {
+            self.catch_up(cycles);
+            return true;
+        }
+
         self.trace();
         let opcode = self.read_next_byte();
+        let success = match self.variant {
+            CpuVariant::Cmos65C02 => self.execute_65c02(opcode),
+            _ => self.execute_nmos(opcode),
+        };
+
+        self.catch_up(cycles);
+
+        success
+    }
+
+    /// Executes an opcode of the NMOS 6502, including the unofficial ones.
+    /// Returns `false` if the CPU has locked up.
+    fn execute_nmos(&mut self, opcode: Byte) -> bool {
         let mut success = true;
 
         match opcode {
@@ -84,6 +123,9 @@ impl<M: Memory> CPU<M> {
             // BPL
             0x10 => self.execute::<BPL, Relative>(),
 
+            // BRK
+            0x00 => self.execute::<BRK, Implied>(),
+
             // BVC
             0x50 => self.execute::<BVC, Relative>(),
 
@@ -196,6 +238,12 @@ impl<M: Memory> CPU<M> {
 
             // NOP
             0xea => self.execute::<NOP, Implied>(),
+            0x1a | 0x3a | 0x5a | 0x7a | 0xda | 0xfa => self.execute::<NOP, Implied>(),
+            0x80 | 0x82 | 0x89 | 0xc2 | 0xe2 => self.execute_read::<NOP, Immediate>(),
+            0x04 | 0x44 | 0x64 => self.execute_read::<NOP, ZeroPage>(),
+            0x14 | 0x34 | 0x54 | 0x74 | 0xd4 | 0xf4 => self.execute_read::<NOP, ZeroPageX>(),
+            0x0c => self.execute_read::<NOP, Absolute>(),
+            0x1c | 0x3c | 0x5c | 0x7c | 0xdc | 0xfc => self.execute_read::<NOP, AbsoluteX>(),
 
             // ORA
             0x09 => self.execute_read::<ORA, Immediate>(),
@@ -248,6 +296,7 @@ impl<M: Memory> CPU<M> {
             0xf9 => self.execute_read::<SBC, AbsoluteY>(),
             0xe1 => self.execute_read::<SBC, IndexedIndirectX>(),
             0xf1 => self.execute_read::<SBC, IndirectIndexedY>(),
+            0xeb => self.execute_read::<SBC, Immediate>(),
 
             // SEC
             0x38 => self.execute::<SEC, Implied>(),
@@ -295,11 +344,236 @@ impl<M: Memory> CPU<M> {
             // TYA
             0x98 => self.execute::<TYA, Implied>(),
 
-            _ => {
+            // UNOFFICIAL OPCODES
+
+            // AHX
+            0x9f => self.execute_write::<AHX, AbsoluteY>(),
+            0x93 => self.execute_write::<AHX, IndirectIndexedY>(),
+
+            // ALR
+            0x4b => self.execute_read::<ALR, Immediate>(),
+
+            // ANC
+            0x0b | 0x2b => self.execute_read::<ANC, Immediate>(),
+
+            // ARR
+            0x6b => self.execute_read::<ARR, Immediate>(),
+
+            // AXS
+            0xcb => self.execute_read::<AXS, Immediate>(),
+
+            // DCP
+            0xc7 => self.execute_read_write::<DCP, ZeroPage>(),
+            0xd7 => self.execute_read_write::<DCP, ZeroPageX>(),
+            0xcf => self.execute_read_write::<DCP, Absolute>(),
+            0xdf => self.execute_read_write::<DCP, AbsoluteX>(),
+            0xdb => self.execute_read_write::<DCP, AbsoluteY>(),
+            0xc3 => self.execute_read_write::<DCP, IndexedIndirectX>(),
+            0xd3 => self.execute_read_write::<DCP, IndirectIndexedY>(),
+
+            // ISB
+            0xe7 => self.execute_read_write::<ISB, ZeroPage>(),
+            0xf7 => self.execute_read_write::<ISB, ZeroPageX>(),
+            0xef => self.execute_read_write::<ISB, Absolute>(),
+            0xff => self.execute_read_write::<ISB, AbsoluteX>(),
+            0xfb => self.execute_read_write::<ISB, AbsoluteY>(),
+            0xe3 => self.execute_read_write::<ISB, IndexedIndirectX>(),
+            0xf3 => self.execute_read_write::<ISB, IndirectIndexedY>(),
+
+            // KIL
+            0x02 | 0x12 | 0x22 | 0x32 | 0x42 | 0x52 | 0x62 | 0x72 | 0x92 | 0xb2 | 0xd2 | 0xf2 => {
+                self.execute::<KIL, Implied>();
                 success = false;
             }
+
+            // LAS
+            0xbb => self.execute_read::<LAS, AbsoluteY>(),
+
+            // LAX
+            0xa7 => self.execute_read::<LAX, ZeroPage>(),
+            0xb7 => self.execute_read::<LAX, ZeroPageY>(),
+            0xaf => self.execute_read::<LAX, Absolute>(),
+            0xbf => self.execute_read::<LAX, AbsoluteY>(),
+            0xa3 => self.execute_read::<LAX, IndexedIndirectX>(),
+            0xb3 => self.execute_read::<LAX, IndirectIndexedY>(),
+
+            // LXA
+            0xab => self.execute_read::<LXA, Immediate>(),
+
+            // RLA
+            0x27 => self.execute_read_write::<RLA, ZeroPage>(),
+            0x37 => self.execute_read_write::<RLA, ZeroPageX>(),
+            0x2f => self.execute_read_write::<RLA, Absolute>(),
+            0x3f => self.execute_read_write::<RLA, AbsoluteX>(),
+            0x3b => self.execute_read_write::<RLA, AbsoluteY>(),
+            0x23 => self.execute_read_write::<RLA, IndexedIndirectX>(),
+            0x33 => self.execute_read_write::<RLA, IndirectIndexedY>(),
+
+            // RRA
+            0x67 => self.execute_read_write::<RRA, ZeroPage>(),
+            0x77 => self.execute_read_write::<RRA, ZeroPageX>(),
+            0x6f => self.execute_read_write::<RRA, Absolute>(),
+            0x7f => self.execute_read_write::<RRA, AbsoluteX>(),
+            0x7b => self.execute_read_write::<RRA, AbsoluteY>(),
+            0x63 => self.execute_read_write::<RRA, IndexedIndirectX>(),
+            0x73 => self.execute_read_write::<RRA, IndirectIndexedY>(),
+
+            // SAX
+            0x87 => self.execute_write::<SAX, ZeroPage>(),
+            0x97 => self.execute_write::<SAX, ZeroPageY>(),
+            0x8f => self.execute_write::<SAX, Absolute>(),
+            0x83 => self.execute_write::<SAX, IndexedIndirectX>(),
+
+            // SHX
+            0x9e => self.execute_write::<SHX, AbsoluteY>(),
+
+            // SHY
+            0x9c => self.execute_write::<SHY, AbsoluteX>(),
+
+            // SLO
+            0x07 => self.execute_read_write::<SLO, ZeroPage>(),
+            0x17 => self.execute_read_write::<SLO, ZeroPageX>(),
+            0x0f => self.execute_read_write::<SLO, Absolute>(),
+            0x1f => self.execute_read_write::<SLO, AbsoluteX>(),
+            0x1b => self.execute_read_write::<SLO, AbsoluteY>(),
+            0x03 => self.execute_read_write::<SLO, IndexedIndirectX>(),
+            0x13 => self.execute_read_write::<SLO, IndirectIndexedY>(),
+
+            // SRE
+            0x47 => self.execute_read_write::<SRE, ZeroPage>(),
+            0x57 => self.execute_read_write::<SRE, ZeroPageX>(),
+            0x4f => self.execute_read_write::<SRE, Absolute>(),
+            0x5f => self.execute_read_write::<SRE, AbsoluteX>(),
+            0x5b => self.execute_read_write::<SRE, AbsoluteY>(),
+            0x43 => self.execute_read_write::<SRE, IndexedIndirectX>(),
+            0x53 => self.execute_read_write::<SRE, IndirectIndexedY>(),
+
+            // TAS
+            0x9b => self.execute_write::<TAS, AbsoluteY>(),
+
+            // XAA
+            0x8b => self.execute_read::<XAA, Immediate>(),
         }
 
         success
     }
+
+    /// Executes an opcode of the 65C02, which gives every opcode the NMOS
+    /// 6502 leaves undefined a new instruction or a NOP.
+    fn execute_65c02(&mut self, opcode: Byte) -> bool {
+        match opcode {
+            // (zp)
+            0x12 => self.execute_read::<ORA, ZeroPageIndirect>(),
+            0x32 => self.execute_read::<AND, ZeroPageIndirect>(),
+            0x52 => self.execute_read::<EOR, ZeroPageIndirect>(),
+            0x72 => self.execute_read::<ADC, ZeroPageIndirect>(),
+            0x92 => self.execute_write::<STA, ZeroPageIndirect>(),
+            0xb2 => self.execute_read::<LDA, ZeroPageIndirect>(),
+            0xd2 => self.execute_read::<CMP, ZeroPageIndirect>(),
+            0xf2 => self.execute_read::<SBC, ZeroPageIndirect>(),
+
+            // BBR
+            0x0f => self.execute::<BBR<0>, ZeroPageRelative>(),
+            0x1f => self.execute::<BBR<1>, ZeroPageRelative>(),
+            0x2f => self.execute::<BBR<2>, ZeroPageRelative>(),
+            0x3f => self.execute::<BBR<3>, ZeroPageRelative>(),
+            0x4f => self.execute::<BBR<4>, ZeroPageRelative>(),
+            0x5f => self.execute::<BBR<5>, ZeroPageRelative>(),
+            0x6f => self.execute::<BBR<6>, ZeroPageRelative>(),
+            0x7f => self.execute::<BBR<7>, ZeroPageRelative>(),
+
+            // BBS
+            0x8f => self.execute::<BBS<0>, ZeroPageRelative>(),
+            0x9f => self.execute::<BBS<1>, ZeroPageRelative>(),
+            0xaf => self.execute::<BBS<2>, ZeroPageRelative>(),
+            0xbf => self.execute::<BBS<3>, ZeroPageRelative>(),
+            0xcf => self.execute::<BBS<4>, ZeroPageRelative>(),
+            0xdf => self.execute::<BBS<5>, ZeroPageRelative>(),
+            0xef => self.execute::<BBS<6>, ZeroPageRelative>(),
+            0xff => self.execute::<BBS<7>, ZeroPageRelative>(),
+
+            // BIT
+            0x89 => self.execute_read::<BIT, Immediate>(),
+            0x34 => self.execute_read::<BIT, ZeroPageX>(),
+            0x3c => self.execute_read::<BIT, AbsoluteX>(),
+
+            // BRA
+            0x80 => self.execute::<BRA, Relative>(),
+
+            // ASL, LSR, ROL, ROR
+            0x1e => self.execute_read_write::<Cmos<ASL>, AbsoluteX>(),
+            0x5e => self.execute_read_write::<Cmos<LSR>, AbsoluteX>(),
+            0x3e => self.execute_read_write::<Cmos<ROL>, AbsoluteX>(),
+            0x7e => self.execute_read_write::<Cmos<ROR>, AbsoluteX>(),
+
+            // DEC, INC
+            0x3a => self.execute_read_write::<DEC, Accumulator>(),
+            0x1a => self.execute_read_write::<INC, Accumulator>(),
+
+            // JMP
+            0x7c => self.execute::<JMPX, AbsoluteIndexedIndirect>(),
+
+            // NOP
+            0x02 | 0x22 | 0x42 | 0x62 | 0x82 | 0xc2 | 0xe2 => self.execute_read::<NOP, Immediate>(),
+            0x44 => self.execute_read::<NOP, ZeroPage>(),
+            0x54 | 0xd4 | 0xf4 => self.execute_read::<NOP, ZeroPageX>(),
+            0xdc | 0xfc => self.execute_read::<NOP, Absolute>(),
+            0x5c => self.execute::<NOP5C, Absolute>(),
+            0x03 | 0x13 | 0x23 | 0x33 | 0x43 | 0x53 | 0x63 | 0x73 | 0x83 | 0x93 | 0xa3 | 0xb3
+            | 0xc3 | 0xd3 | 0xe3 | 0xf3 | 0x0b | 0x1b | 0x2b | 0x3b | 0x4b | 0x5b | 0x6b | 0x7b
+            | 0x8b | 0x9b | 0xab | 0xbb | 0xeb | 0xfb => self.execute::<NOP1, Implied>(),
+
+            // PHX, PHY, PLX, PLY
+            0xda => self.execute::<PHX, Implied>(),
+            0x5a => self.execute::<PHY, Implied>(),
+            0xfa => self.execute::<PLX, Implied>(),
+            0x7a => self.execute::<PLY, Implied>(),
+
+            // RMB
+            0x07 => self.execute_read_write::<RMB<0>, ZeroPage>(),
+            0x17 => self.execute_read_write::<RMB<1>, ZeroPage>(),
+            0x27 => self.execute_read_write::<RMB<2>, ZeroPage>(),
+            0x37 => self.execute_read_write::<RMB<3>, ZeroPage>(),
+            0x47 => self.execute_read_write::<RMB<4>, ZeroPage>(),
+            0x57 => self.execute_read_write::<RMB<5>, ZeroPage>(),
+            0x67 => self.execute_read_write::<RMB<6>, ZeroPage>(),
+            0x77 => self.execute_read_write::<RMB<7>, ZeroPage>(),
+
+            // SMB
+            0x87 => self.execute_read_write::<SMB<0>, ZeroPage>(),
+            0x97 => self.execute_read_write::<SMB<1>, ZeroPage>(),
+            0xa7 => self.execute_read_write::<SMB<2>, ZeroPage>(),
+            0xb7 => self.execute_read_write::<SMB<3>, ZeroPage>(),
+            0xc7 => self.execute_read_write::<SMB<4>, ZeroPage>(),
+            0xd7 => self.execute_read_write::<SMB<5>, ZeroPage>(),
+            0xe7 => self.execute_read_write::<SMB<6>, ZeroPage>(),
+            0xf7 => self.execute_read_write::<SMB<7>, ZeroPage>(),
+
+            // STP
+            0xdb => {
+                self.execute::<STP, Implied>();
+                return false;
+            }
+
+            // STZ
+            0x64 => self.execute_write::<STZ, ZeroPage>(),
+            0x74 => self.execute_write::<STZ, ZeroPageX>(),
+            0x9c => self.execute_write::<STZ, Absolute>(),
+            0x9e => self.execute_write::<STZ, AbsoluteX>(),
+
+            // TRB, TSB
+            0x14 => self.execute_read_write::<TRB, ZeroPage>(),
+            0x1c => self.execute_read_write::<TRB, Absolute>(),
+            0x04 => self.execute_read_write::<TSB, ZeroPage>(),
+            0x0c => self.execute_read_write::<TSB, Absolute>(),
+
+            // WAI
+            0xcb => self.execute::<WAI, Implied>(),
+
+            // Everything else is shared with the NMOS 6502.
+            _ => return self.execute_nmos(opcode),
+        }
+
+        true
+    }
 }
@@ -0,0 +1,20 @@
+/// The member of the 6502 family being emulated.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum CpuVariant {
+    /// The NES CPU: an NMOS 6502 with the decimal mode circuitry cut out.
+    #[default]
+    Ricoh2A03,
+    /// The original NMOS 6502. In decimal mode N, V and Z are left as if the
+    /// addition or subtraction had been done in binary.
+    Nmos6502,
+    /// The WDC 65C02, with its extra instructions and addressing mode. It fixes
+    /// the JMP ($xxFF) bug and sets N and Z from the decimal result.
+    Cmos65C02,
+}
+
+impl CpuVariant {
+    /// Whether ADC and SBC honour the decimal flag.
+    pub fn has_decimal_mode(self) -> bool {
+        self != CpuVariant::Ricoh2A03
+    }
+}
@@ -1,4 +1,9 @@
+pub mod apu;
+pub mod bus;
+pub mod controller;
 pub mod cpu;
+pub mod mapper;
 pub mod memory;
+pub mod ppu;
 
 pub mod cartridge;
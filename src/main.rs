@@ -1,16 +1,14 @@
-use famines::{
-    cartridge::Cartridge,
-    cpu::CPU,
-    memory::{ram::RAM, Byte},
-};
+use famines::{bus::Bus, cartridge::Cartridge, cpu::CPU, memory::Byte};
 
 fn main() {
-    let bytes: Vec<Byte> = std::fs::read("res/nestest.nes").unwrap();
+    let path = "res/nestest.nes";
+    let bytes: Vec<Byte> = std::fs::read(path).unwrap();
     let cartridge = Cartridge::new(&bytes).unwrap();
 
-    let mut cpu = CPU::new(cartridge, RAM::new());
+    let mut cpu = CPU::new(Bus::new(cartridge));
     cpu.reset();
     cpu.registers.pc = 0xC000;
+    cpu.tracing = true;
 
     loop {
         if !cpu.step() {
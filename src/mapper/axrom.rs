@@ -0,0 +1,68 @@
+use crate::{
+    cartridge::Mirroring,
+    memory::{Address, Byte},
+};
+
+use super::{Mapper, Mapping};
+
+const PRG_BANK_SIZE: usize = 0x8000;
+
+/// Mapper 7 (AxROM): switchable 32KB PRG and single-screen mirroring.
+pub struct AxROM {
+    prg_size: usize,
+    chr_size: usize,
+    bank: Byte,
+    bus_conflicts: bool,
+}
+
+impl AxROM {
+    const BANK: u8 = 0b111;
+    const NAMETABLE: u8 = 1 << 4;
+
+    pub fn new(prg_size: usize, chr_size: usize, bus_conflicts: bool) -> Self {
+        Self {
+            prg_size,
+            chr_size,
+            bank: 0,
+            bus_conflicts,
+        }
+    }
+}
+
+impl Mapper for AxROM {
+    fn cpu_read(&mut self, address: Address) -> Mapping {
+        match address {
+            0x8000..=0xFFFF => {
+                let bank = (self.bank & Self::BANK) as usize;
+                Mapping::Prg(
+                    (bank * PRG_BANK_SIZE + (address as usize - 0x8000)) % self.prg_size.max(1),
+                )
+            }
+            _ => Mapping::None,
+        }
+    }
+
+    fn cpu_write(&mut self, address: Address, value: Byte) -> Mapping {
+        if address >= 0x8000 {
+            self.bank = value;
+        }
+
+        Mapping::None
+    }
+
+    fn ppu_read(&mut self, address: Address) -> usize {
+        address as usize % self.chr_size.max(1)
+    }
+
+    fn mirroring(&self) -> Option<Mirroring> {
+        Some(if self.bank & Self::NAMETABLE == 0 {
+            Mirroring::SingleScreenA
+        } else {
+            Mirroring::SingleScreenB
+        })
+    }
+
+    fn bus_conflicts(&self) -> bool {
+        self.bus_conflicts
+    }
+}
@@ -0,0 +1,49 @@
+use crate::memory::{Address, Byte};
+
+use super::{Mapper, Mapping};
+
+const CHR_BANK_SIZE: usize = 0x2000;
+
+/// Mapper 3 (CNROM): fixed PRG like NROM, switchable 8KB CHR.
+pub struct CNROM {
+    prg_size: usize,
+    chr_size: usize,
+    bank: Byte,
+}
+
+impl CNROM {
+    pub fn new(prg_size: usize, chr_size: usize) -> Self {
+        Self {
+            prg_size,
+            chr_size,
+            bank: 0,
+        }
+    }
+}
+
+impl Mapper for CNROM {
+    fn cpu_read(&mut self, address: Address) -> Mapping {
+        match address {
+            0x8000..=0xFFFF if self.prg_size > 0 => {
+                Mapping::Prg((address as usize - 0x8000) % self.prg_size)
+            }
+            _ => Mapping::None,
+        }
+    }
+
+    fn cpu_write(&mut self, address: Address, value: Byte) -> Mapping {
+        if address >= 0x8000 {
+            self.bank = value;
+        }
+
+        Mapping::None
+    }
+
+    fn ppu_read(&mut self, address: Address) -> usize {
+        (self.bank as usize * CHR_BANK_SIZE + address as usize) % self.chr_size.max(1)
+    }
+
+    fn bus_conflicts(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,57 @@
+use crate::memory::{Address, Byte};
+
+use super::{Mapper, Mapping};
+
+const PRG_BANK_SIZE: usize = 0x8000;
+const CHR_BANK_SIZE: usize = 0x2000;
+
+/// Mapper 66 (GxROM): switchable 32KB PRG and 8KB CHR from a single register.
+pub struct GxROM {
+    prg_size: usize,
+    chr_size: usize,
+    bank: Byte,
+}
+
+impl GxROM {
+    const CHR_BANK: u8 = 0b0000_0011;
+    const PRG_BANK: u8 = 0b0011_0000;
+
+    pub fn new(prg_size: usize, chr_size: usize) -> Self {
+        Self {
+            prg_size,
+            chr_size,
+            bank: 0,
+        }
+    }
+}
+
+impl Mapper for GxROM {
+    fn cpu_read(&mut self, address: Address) -> Mapping {
+        match address {
+            0x8000..=0xFFFF => {
+                let bank = ((self.bank & Self::PRG_BANK) >> 4) as usize;
+                Mapping::Prg(
+                    (bank * PRG_BANK_SIZE + (address as usize - 0x8000)) % self.prg_size.max(1),
+                )
+            }
+            _ => Mapping::None,
+        }
+    }
+
+    fn cpu_write(&mut self, address: Address, value: Byte) -> Mapping {
+        if address >= 0x8000 {
+            self.bank = value;
+        }
+
+        Mapping::None
+    }
+
+    fn ppu_read(&mut self, address: Address) -> usize {
+        let bank = (self.bank & Self::CHR_BANK) as usize;
+        (bank * CHR_BANK_SIZE + address as usize) % self.chr_size.max(1)
+    }
+
+    fn bus_conflicts(&self) -> bool {
+        true
+    }
+}
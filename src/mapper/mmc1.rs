@@ -0,0 +1,150 @@
+use crate::{
+    cartridge::Mirroring,
+    memory::{Address, Byte},
+};
+
+use super::{Mapper, Mapping};
+
+const PRG_BANK_SIZE: usize = 0x4000;
+const CHR_BANK_SIZE: usize = 0x1000;
+const PRG_RAM_BANK_SIZE: usize = 0x2000;
+
+/// Mapper 1 (MMC1, SxROM), including the 512KB SUROM/SXROM boards.
+pub struct MMC1 {
+    prg_size: usize,
+    chr_size: usize,
+    prg_ram_size: usize,
+    shift: Byte,
+    control: Byte,
+    chr_bank_0: Byte,
+    chr_bank_1: Byte,
+    prg_bank: Byte,
+}
+
+impl MMC1 {
+    const SHIFT_RESET: u8 = 0b1_0000;
+
+    const CONTROL_MIRRORING: u8 = 0b11;
+    const CONTROL_PRG_MODE: u8 = 0b1100;
+    const CONTROL_CHR_4KB: u8 = 1 << 4;
+
+    const PRG_RAM_DISABLE: u8 = 1 << 4;
+
+    pub fn new(prg_size: usize, chr_size: usize, prg_ram_size: usize) -> Self {
+        Self {
+            prg_size,
+            chr_size,
+            prg_ram_size,
+            shift: Self::SHIFT_RESET,
+            control: Self::CONTROL_PRG_MODE, // Fix the last bank at $C000.
+            chr_bank_0: 0,
+            chr_bank_1: 0,
+            prg_bank: 0,
+        }
+    }
+
+    fn write_register(&mut self, address: Address, value: Byte) {
+        match address {
+            0x8000..=0x9FFF => self.control = value,
+            0xA000..=0xBFFF => self.chr_bank_0 = value,
+            0xC000..=0xDFFF => self.chr_bank_1 = value,
+            _ => self.prg_bank = value,
+        }
+    }
+
+    /// SUROM/SXROM use bit 4 of the CHR register to select a 256KB PRG half.
+    fn prg_outer_bank(&self) -> usize {
+        if self.prg_size > 0x40000 {
+            (self.chr_bank_0 & 0x10) as usize >> 4
+        } else {
+            0
+        }
+    }
+
+    fn prg_offset(&self, address: Address) -> usize {
+        let last = (0x40000.min(self.prg_size) / PRG_BANK_SIZE).saturating_sub(1);
+        let bank = (self.prg_bank & 0x0F) as usize;
+
+        let bank = match ((self.control & Self::CONTROL_PRG_MODE) >> 2, address) {
+            (0 | 1, 0x8000..=0xBFFF) => bank & !1,
+            (0 | 1, _) => bank | 1,
+            (2, 0x8000..=0xBFFF) => 0,
+            (2, _) => bank,
+            (_, 0x8000..=0xBFFF) => bank,
+            (_, _) => last,
+        };
+
+        let bank = bank + self.prg_outer_bank() * (0x40000 / PRG_BANK_SIZE);
+        (bank * PRG_BANK_SIZE + (address as usize & (PRG_BANK_SIZE - 1))) % self.prg_size.max(1)
+    }
+
+    /// Boards with more than 8KB of PRG-RAM bank it with the CHR register:
+    /// bit 3 on the 16KB SOROM, bits 2-3 on the 32KB SXROM. Other boards
+    /// use those bits for CHR banking, so they always see the first bank.
+    fn prg_ram_bank(&self) -> usize {
+        match self.prg_ram_size {
+            0..=PRG_RAM_BANK_SIZE => 0,
+            0x2001..=0x4000 => ((self.chr_bank_0 >> 3) & 0x01) as usize,
+            _ => ((self.chr_bank_0 >> 2) & 0x03) as usize,
+        }
+    }
+
+    fn prg_ram_enabled(&self) -> bool {
+        self.prg_bank & Self::PRG_RAM_DISABLE == 0
+    }
+}
+
+impl Mapper for MMC1 {
+    fn cpu_read(&mut self, address: Address) -> Mapping {
+        match address {
+            0x6000..=0x7FFF if self.prg_ram_enabled() => {
+                Mapping::Ram(self.prg_ram_bank() * PRG_RAM_BANK_SIZE + (address as usize - 0x6000))
+            }
+            0x8000..=0xFFFF => Mapping::Prg(self.prg_offset(address)),
+            _ => Mapping::None,
+        }
+    }
+
+    fn cpu_write(&mut self, address: Address, value: Byte) -> Mapping {
+        if address < 0x8000 {
+            return self.cpu_read(address);
+        }
+
+        if value & 0x80 != 0 {
+            self.shift = Self::SHIFT_RESET;
+            self.control |= Self::CONTROL_PRG_MODE;
+            return Mapping::None;
+        }
+
+        let full = self.shift & 0x01 != 0;
+        self.shift = (self.shift >> 1) | ((value & 0x01) << 4);
+        if full {
+            self.write_register(address, self.shift);
+            self.shift = Self::SHIFT_RESET;
+        }
+
+        Mapping::None
+    }
+
+    fn ppu_read(&mut self, address: Address) -> usize {
+        let bank = if self.control & Self::CONTROL_CHR_4KB != 0 {
+            match address {
+                0x0000..=0x0FFF => self.chr_bank_0 as usize,
+                _ => self.chr_bank_1 as usize,
+            }
+        } else {
+            (self.chr_bank_0 & !1) as usize + (address as usize >> 12)
+        };
+
+        (bank * CHR_BANK_SIZE + (address as usize & (CHR_BANK_SIZE - 1))) % self.chr_size.max(1)
+    }
+
+    fn mirroring(&self) -> Option<Mirroring> {
+        Some(match self.control & Self::CONTROL_MIRRORING {
+            0 => Mirroring::SingleScreenA,
+            1 => Mirroring::SingleScreenB,
+            2 => Mirroring::Vertical,
+            _ => Mirroring::Horizontal,
+        })
+    }
+}
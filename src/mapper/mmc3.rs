@@ -0,0 +1,191 @@
+use crate::{
+    cartridge::Mirroring,
+    memory::{Address, Byte},
+};
+
+use super::{Mapper, Mapping};
+
+const PRG_BANK_SIZE: usize = 0x2000;
+const CHR_BANK_SIZE: usize = 0x0400;
+
+/// How long A12 must stay low before a rising edge clocks the counter. The
+/// MMC3 waits for about 3 falling edges of M2, so the close together edges
+/// of mixed 8x16 sprite fetches or $2007 accesses are ignored.
+const A12_FILTER_DOTS: usize = 10;
+
+/// Mapper 4 (MMC3, TxROM), with its A12 driven scanline counter.
+pub struct MMC3 {
+    prg_size: usize,
+    chr_size: usize,
+    four_screen: bool,
+    bank_select: Byte,
+    banks: [Byte; 8],
+    mirroring: Mirroring,
+    prg_ram_protect: Byte,
+    irq_latch: Byte,
+    irq_counter: Byte,
+    irq_reload: bool,
+    irq_enabled: bool,
+    irq_pending: bool,
+    a12: bool,
+    a12_low_dots: usize,
+}
+
+impl MMC3 {
+    const SELECT_REGISTER: u8 = 0b111;
+    const SELECT_PRG_MODE: u8 = 1 << 6;
+    const SELECT_CHR_INVERSION: u8 = 1 << 7;
+
+    const PRG_RAM_WRITE_PROTECT: u8 = 1 << 6;
+    const PRG_RAM_ENABLE: u8 = 1 << 7;
+
+    pub fn new(prg_size: usize, chr_size: usize, mirroring: Mirroring) -> Self {
+        Self {
+            prg_size,
+            chr_size,
+            four_screen: mirroring == Mirroring::FourScreen,
+            bank_select: 0,
+            banks: [0, 2, 4, 5, 6, 7, 0, 1],
+            mirroring,
+            prg_ram_protect: Self::PRG_RAM_ENABLE,
+            irq_latch: 0,
+            irq_counter: 0,
+            irq_reload: false,
+            irq_enabled: false,
+            irq_pending: false,
+            a12: false,
+            a12_low_dots: 0,
+        }
+    }
+
+    fn prg_offset(&self, address: Address) -> usize {
+        let second_last = (self.prg_size / PRG_BANK_SIZE).saturating_sub(2);
+        let r6 = (self.banks[6] & 0x3F) as usize;
+        let r7 = (self.banks[7] & 0x3F) as usize;
+
+        let swapped = self.bank_select & Self::SELECT_PRG_MODE != 0;
+        let bank = match (address, swapped) {
+            (0x8000..=0x9FFF, false) => r6,
+            (0x8000..=0x9FFF, true) => second_last,
+            (0xA000..=0xBFFF, _) => r7,
+            (0xC000..=0xDFFF, false) => second_last,
+            (0xC000..=0xDFFF, true) => r6,
+            (_, _) => second_last + 1,
+        };
+
+        (bank * PRG_BANK_SIZE + (address as usize & (PRG_BANK_SIZE - 1))) % self.prg_size.max(1)
+    }
+
+    fn chr_offset(&self, address: Address) -> usize {
+        let mut address = address as usize;
+        if self.bank_select & Self::SELECT_CHR_INVERSION != 0 {
+            address ^= 0x1000;
+        }
+
+        let bank = match address {
+            0x0000..=0x07FF => (self.banks[0] & !1) as usize + (address >> 10 & 1),
+            0x0800..=0x0FFF => (self.banks[1] & !1) as usize + (address >> 10 & 1),
+            _ => self.banks[2 + ((address - 0x1000) >> 10)] as usize,
+        };
+
+        (bank * CHR_BANK_SIZE + (address & (CHR_BANK_SIZE - 1))) % self.chr_size.max(1)
+    }
+
+    /// Clocks the scanline counter on rising edges of PPU A12, once it has
+    /// been low for long enough.
+    fn observe(&mut self, address: Address) {
+        let a12 = address & 0x1000 != 0;
+        if a12 {
+            if !self.a12 && self.a12_low_dots >= A12_FILTER_DOTS {
+                self.clock_counter();
+            }
+            self.a12_low_dots = 0;
+        }
+        self.a12 = a12;
+    }
+
+    fn clock_counter(&mut self) {
+        if self.irq_counter == 0 || self.irq_reload {
+            self.irq_counter = self.irq_latch;
+            self.irq_reload = false;
+        } else {
+            self.irq_counter -= 1;
+        }
+
+        if self.irq_counter == 0 && self.irq_enabled {
+            self.irq_pending = true;
+        }
+    }
+}
+
+impl Mapper for MMC3 {
+    fn cpu_read(&mut self, address: Address) -> Mapping {
+        match address {
+            0x6000..=0x7FFF if self.prg_ram_protect & Self::PRG_RAM_ENABLE != 0 => {
+                Mapping::Ram(address as usize - 0x6000)
+            }
+            0x8000..=0xFFFF => Mapping::Prg(self.prg_offset(address)),
+            _ => Mapping::None,
+        }
+    }
+
+    fn cpu_write(&mut self, address: Address, value: Byte) -> Mapping {
+        let even = address & 0x01 == 0;
+        match address {
+            0x6000..=0x7FFF => {
+                if self.prg_ram_protect & Self::PRG_RAM_WRITE_PROTECT != 0 {
+                    return Mapping::None;
+                }
+                return self.cpu_read(address);
+            }
+            0x8000..=0x9FFF if even => self.bank_select = value,
+            0x8000..=0x9FFF => {
+                self.banks[(self.bank_select & Self::SELECT_REGISTER) as usize] = value;
+            }
+            0xA000..=0xBFFF if even => {
+                self.mirroring = if value & 0x01 == 0 {
+                    Mirroring::Vertical
+                } else {
+                    Mirroring::Horizontal
+                };
+            }
+            0xA000..=0xBFFF => self.prg_ram_protect = value,
+            0xC000..=0xDFFF if even => self.irq_latch = value,
+            0xC000..=0xDFFF => {
+                self.irq_counter = 0;
+                self.irq_reload = true;
+            }
+            0xE000..=0xFFFF if even => {
+                self.irq_enabled = false;
+                self.irq_pending = false;
+            }
+            0xE000..=0xFFFF => self.irq_enabled = true,
+            _ => {}
+        }
+
+        Mapping::None
+    }
+
+    fn ppu_read(&mut self, address: Address) -> usize {
+        self.observe(address);
+        self.chr_offset(address)
+    }
+
+    fn ppu_tick(&mut self) {
+        if !self.a12 {
+            self.a12_low_dots = self.a12_low_dots.saturating_add(1);
+        }
+    }
+
+    fn mirroring(&self) -> Option<Mirroring> {
+        if self.four_screen {
+            None
+        } else {
+            Some(self.mirroring)
+        }
+    }
+
+    fn irq(&self) -> bool {
+        self.irq_pending
+    }
+}
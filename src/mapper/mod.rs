@@ -0,0 +1,79 @@
+use crate::{
+    cartridge::{header::CartridgeHeader, Mirroring},
+    memory::{Address, Byte},
+};
+
+use self::{
+    axrom::AxROM, cnrom::CNROM, gxrom::GxROM, mmc1::MMC1, mmc3::MMC3, nrom::NROM, uxrom::UxROM,
+};
+
+pub mod axrom;
+pub mod cnrom;
+pub mod gxrom;
+pub mod mmc1;
+pub mod mmc3;
+pub mod nrom;
+pub mod uxrom;
+
+/// Where a CPU access to the cartridge space ($4020-$FFFF) lands.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Mapping {
+    Prg(usize),
+    Ram(usize),
+    None,
+}
+
+/// Bank switching logic of a cartridge board. The cartridge owns the actual
+/// memories, a mapper only translates addresses into offsets within them.
+pub trait Mapper {
+    fn cpu_read(&mut self, address: Address) -> Mapping;
+
+    /// Called for every CPU write to the cartridge space, so that the mapper
+    /// can update its registers. Returns where the value should be stored.
+    fn cpu_write(&mut self, address: Address, value: Byte) -> Mapping;
+
+    /// Maps a PPU address in $0000-$1FFF to an offset within CHR memory.
+    fn ppu_read(&mut self, address: Address) -> usize;
+
+    fn ppu_write(&mut self, address: Address) -> usize {
+        self.ppu_read(address)
+    }
+
+    /// Called once per PPU dot, for mappers that time the PPU address bus.
+    fn ppu_tick(&mut self) {}
+
+    /// The mirroring selected by the mapper, if it overrides the header.
+    fn mirroring(&self) -> Option<Mirroring> {
+        None
+    }
+
+    fn irq(&self) -> bool {
+        false
+    }
+
+    /// Whether the board lets the PRG-ROM drive the data bus during register
+    /// writes, in which case the written value is ANDed with the ROM byte.
+    fn bus_conflicts(&self) -> bool {
+        false
+    }
+}
+
+/// Creates the mapper described by the header, if supported.
+pub fn new(header: &CartridgeHeader) -> Option<Box<dyn Mapper>> {
+    let prg_size = header.prg_rom_size;
+    let chr_size = header.chr_size();
+    let prg_ram_size = header.prg_ram_size + header.prg_nvram_size;
+
+    match header.mapper {
+        0 => Some(Box::new(NROM::new(prg_size, chr_size))),
+        1 => Some(Box::new(MMC1::new(prg_size, chr_size, prg_ram_size))),
+        // Submapper 1 has no bus conflicts, 2 has them. Unspecified boards
+        // default to the most common variant.
+        2 => Some(Box::new(UxROM::new(prg_size, chr_size, header.submapper != 1))),
+        3 => Some(Box::new(CNROM::new(prg_size, chr_size))),
+        4 => Some(Box::new(MMC3::new(prg_size, chr_size, header.mirroring))),
+        7 => Some(Box::new(AxROM::new(prg_size, chr_size, header.submapper == 2))),
+        66 => Some(Box::new(GxROM::new(prg_size, chr_size))),
+        _ => None,
+    }
+}
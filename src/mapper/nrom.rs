@@ -0,0 +1,39 @@
+use crate::memory::{Address, Byte};
+
+use super::{Mapper, Mapping};
+
+/// Mapper 0: 16KB (mirrored) or 32KB of PRG-ROM and 8KB of CHR, no banking.
+/// A few boards (e.g. Family BASIC) add PRG-RAM at $6000-$7FFF.
+pub struct NROM {
+    prg_size: usize,
+    chr_size: usize,
+}
+
+impl NROM {
+    pub fn new(prg_size: usize, chr_size: usize) -> Self {
+        Self { prg_size, chr_size }
+    }
+}
+
+impl Mapper for NROM {
+    fn cpu_read(&mut self, address: Address) -> Mapping {
+        match address {
+            0x6000..=0x7FFF => Mapping::Ram(address as usize - 0x6000),
+            0x8000..=0xFFFF if self.prg_size > 0 => {
+                Mapping::Prg((address as usize - 0x8000) % self.prg_size)
+            }
+            _ => Mapping::None,
+        }
+    }
+
+    fn cpu_write(&mut self, address: Address, _value: Byte) -> Mapping {
+        match address {
+            0x6000..=0x7FFF => Mapping::Ram(address as usize - 0x6000),
+            _ => Mapping::None,
+        }
+    }
+
+    fn ppu_read(&mut self, address: Address) -> usize {
+        address as usize % self.chr_size.max(1)
+    }
+}
@@ -0,0 +1,55 @@
+use crate::memory::{Address, Byte};
+
+use super::{Mapper, Mapping};
+
+const PRG_BANK_SIZE: usize = 0x4000;
+
+/// Mapper 2 (UxROM): switchable 16KB at $8000, last bank fixed at $C000.
+pub struct UxROM {
+    prg_size: usize,
+    chr_size: usize,
+    bank: Byte,
+    bus_conflicts: bool,
+}
+
+impl UxROM {
+    pub fn new(prg_size: usize, chr_size: usize, bus_conflicts: bool) -> Self {
+        Self {
+            prg_size,
+            chr_size,
+            bank: 0,
+            bus_conflicts,
+        }
+    }
+}
+
+impl Mapper for UxROM {
+    fn cpu_read(&mut self, address: Address) -> Mapping {
+        let bank = match address {
+            0x8000..=0xBFFF => self.bank as usize,
+            0xC000..=0xFFFF => (self.prg_size / PRG_BANK_SIZE).saturating_sub(1),
+            _ => return Mapping::None,
+        };
+
+        Mapping::Prg(
+            (bank * PRG_BANK_SIZE + (address as usize & (PRG_BANK_SIZE - 1)))
+                % self.prg_size.max(1),
+        )
+    }
+
+    fn cpu_write(&mut self, address: Address, value: Byte) -> Mapping {
+        if address >= 0x8000 {
+            self.bank = value;
+        }
+
+        Mapping::None
+    }
+
+    fn ppu_read(&mut self, address: Address) -> usize {
+        address as usize % self.chr_size.max(1)
+    }
+
+    fn bus_conflicts(&self) -> bool {
+        self.bus_conflicts
+    }
+}
@@ -1,43 +1,63 @@
-use crate::cpu::CPU;
+use crate::bus::CpuBus;
+use crate::cpu::{CpuVariant, CPU};
 use crate::memory::Address;
 use crate::memory::Byte;
 use crate::memory::Memory;
 use crate::memory::Word;
 use crate::memory::ZeroPageMemory;
 
-pub trait Addressing<M: Memory> {
-    fn create_addressing(cpu: &mut CPU<M>, page_boundary: bool) -> Self;
+pub trait Addressing<B: CpuBus> {
+    fn create_addressing(cpu: &mut CPU<B>, page_boundary: bool) -> Self;
     fn get_address(&self) -> Option<Address>;
 }
 
-pub trait ReadMode<M: Memory> {
-    fn read(&self, cpu: &mut CPU<M>) -> Byte
+pub trait ReadMode<B: CpuBus> {
+    fn read(&self, cpu: &mut CPU<B>) -> Byte
     where
-        Self: Addressing<M> + Sized,
+        Self: Addressing<B> + Sized,
     {
-        cpu.memory.read_byte(self.get_address().unwrap())
+        cpu.read_byte(self.get_address().unwrap())
     }
 }
 
-pub trait WriteMode<M: Memory> {
-    fn write(&self, cpu: &mut CPU<M>, value: Byte)
+pub trait WriteMode<B: CpuBus> {
+    fn write(&self, cpu: &mut CPU<B>, value: Byte)
     where
-        Self: Addressing<M> + Sized,
+        Self: Addressing<B> + Sized,
     {
-        cpu.memory.write_byte(self.get_address().unwrap(), value);
+        cpu.write_byte(self.get_address().unwrap(), value);
+    }
+
+    /// RMW instructions write the unmodified value back before the result.
+    /// The 65C02 reads it a second time instead.
+    fn dummy_write(&self, cpu: &mut CPU<B>, value: Byte)
+    where
+        Self: Addressing<B> + Sized,
+    {
+        if let Some(address) = self.get_address() {
+            if cpu.variant == CpuVariant::Cmos65C02 {
+                cpu.dummy_read(address);
+            } else {
+                cpu.dummy_write(address, value);
+            }
+        }
     }
 }
 
 pub struct Implied;
 pub struct Relative;
 pub struct Indirect;
+/// JMP (abs,X), 65C02 only.
+pub struct AbsoluteIndexedIndirect;
+/// The zero page address and branch offset of BBR/BBS, 65C02 only.
+pub struct ZeroPageRelative;
 
 // IMMEDIATE
 
 pub struct Immediate;
 
-impl<M: Memory> Addressing<M> for Immediate {
-    fn create_addressing(_cpu: &mut CPU<M>, _page_boundary: bool) -> Self {
+impl<B: CpuBus> Addressing<B> for Immediate {
+    fn create_addressing(_cpu: &mut CPU<B>, _page_boundary: bool) -> Self {
         Self
     }
 
@@ -46,8 +66,8 @@ impl<M: Memory> Addressing<M> for Immediate {
     }
 }
 
-impl<M: Memory> ReadMode<M> for Immediate {
-    fn read(&self, cpu: &mut CPU<M>) -> Byte {
+impl<B: CpuBus> ReadMode<B> for Immediate {
+    fn read(&self, cpu: &mut CPU<B>) -> Byte {
         cpu.read_next_byte()
     }
 }
@@ -56,8 +76,9 @@ impl<M: Memory> ReadMode<M> for Immediate {
 
 pub struct Accumulator;
 
-impl<M: Memory> Addressing<M> for Accumulator {
-    fn create_addressing(_cpu: &mut CPU<M>, _page_boundary: bool) -> Self {
+impl<B: CpuBus> Addressing<B> for Accumulator {
+    fn create_addressing(cpu: &mut CPU<B>, _page_boundary: bool) -> Self {
+        cpu.idle_read();
         Self
     }
 
@@ -66,14 +87,14 @@ impl<M: Memory> Addressing<M> for Accumulator {
     }
 }
 
-impl<M: Memory> ReadMode<M> for Accumulator {
-    fn read(&self, cpu: &mut CPU<M>) -> Byte {
+impl<B: CpuBus> ReadMode<B> for Accumulator {
+    fn read(&self, cpu: &mut CPU<B>) -> Byte {
         cpu.registers.a
     }
 }
 
-impl<M: Memory> WriteMode<M> for Accumulator {
-    fn write(&self, cpu: &mut CPU<M>, value: Byte) {
+impl<B: CpuBus> WriteMode<B> for Accumulator {
+    fn write(&self, cpu: &mut CPU<B>, value: Byte) {
         cpu.registers.a = value;
     }
 }
@@ -84,8 +105,8 @@ pub struct ZeroPage {
     address: Address,
 }
 
-impl<M: Memory> Addressing<M> for ZeroPage {
-    fn create_addressing(cpu: &mut CPU<M>, _page_boundary: bool) -> Self {
+impl<B: CpuBus> Addressing<B> for ZeroPage {
+    fn create_addressing(cpu: &mut CPU<B>, _page_boundary: bool) -> Self {
         Self {
             address: cpu.read_next_byte() as Word,
         }
@@ -96,8 +117,8 @@ impl<M: Memory> Addressing<M> for ZeroPage {
     }
 }
 
-impl<M: Memory> ReadMode<M> for ZeroPage {}
-impl<M: Memory> WriteMode<M> for ZeroPage {}
+impl<B: CpuBus> ReadMode<B> for ZeroPage {}
+impl<B: CpuBus> WriteMode<B> for ZeroPage {}
 
 // ZERO_PAGE_X
 
@@ -105,10 +126,12 @@ pub struct ZeroPageX {
     address: Address,
 }
 
-impl<M: Memory> Addressing<M> for ZeroPageX {
-    fn create_addressing(cpu: &mut CPU<M>, _page_boundary: bool) -> Self {
+impl<B: CpuBus> Addressing<B> for ZeroPageX {
+    fn create_addressing(cpu: &mut CPU<B>, _page_boundary: bool) -> Self {
+        let base = cpu.read_next_byte();
+        cpu.dummy_read(base as Word);
         Self {
-            address: (cpu.read_next_byte().wrapping_add(cpu.registers.x)) as Word,
+            address: (base.wrapping_add(cpu.registers.x)) as Word,
         }
     }
 
@@ -117,8 +140,8 @@ impl<M: Memory> Addressing<M> for ZeroPageX {
     }
 }
 
-impl<M: Memory> ReadMode<M> for ZeroPageX {}
-impl<M: Memory> WriteMode<M> for ZeroPageX {}
+impl<B: CpuBus> ReadMode<B> for ZeroPageX {}
+impl<B: CpuBus> WriteMode<B> for ZeroPageX {}
 
 // ZERO_PAGE_Y
 
@@ -126,10 +149,12 @@ pub struct ZeroPageY {
     address: Address,
 }
 
-impl<M: Memory> Addressing<M> for ZeroPageY {
-    fn create_addressing(cpu: &mut CPU<M>, _page_boundary: bool) -> Self {
+impl<B: CpuBus> Addressing<B> for ZeroPageY {
+    fn create_addressing(cpu: &mut CPU<B>, _page_boundary: bool) -> Self {
+        let base = cpu.read_next_byte();
+        cpu.dummy_read(base as Word);
         Self {
-            address: (cpu.read_next_byte().wrapping_add(cpu.registers.y)) as Word,
+            address: (base.wrapping_add(cpu.registers.y)) as Word,
         }
     }
 
@@ -138,8 +163,8 @@ impl<M: Memory> Addressing<M> for ZeroPageY {
     }
 }
 
-impl<M: Memory> ReadMode<M> for ZeroPageY {}
-impl<M: Memory> WriteMode<M> for ZeroPageY {}
+impl<B: CpuBus> ReadMode<B> for ZeroPageY {}
+impl<B: CpuBus> WriteMode<B> for ZeroPageY {}
 
 // ABSOLUTE
 
@@ -147,8 +172,8 @@ pub struct Absolute {
     address: Address,
 }
 
-impl<M: Memory> Addressing<M> for Absolute {
-    fn create_addressing(cpu: &mut CPU<M>, _page_boundary: bool) -> Self {
+impl<B: CpuBus> Addressing<B> for Absolute {
+    fn create_addressing(cpu: &mut CPU<B>, _page_boundary: bool) -> Self {
         Self {
             address: cpu.read_next_word(),
         }
@@ -159,8 +184,8 @@ impl<M: Memory> Addressing<M> for Absolute {
     }
 }
 
-impl<M: Memory> ReadMode<M> for Absolute {}
-impl<M: Memory> WriteMode<M> for Absolute {}
+impl<B: CpuBus> ReadMode<B> for Absolute {}
+impl<B: CpuBus> WriteMode<B> for Absolute {}
 
 // ABSOLUTE_X
 
@@ -168,17 +193,13 @@ pub struct AbsoluteX {
     address: Address,
 }
 
-impl<M: Memory> Addressing<M> for AbsoluteX {
-    fn create_addressing(cpu: &mut CPU<M>, page_boundary: bool) -> Self {
+impl<B: CpuBus> Addressing<B> for AbsoluteX {
+    fn create_addressing(cpu: &mut CPU<B>, page_boundary: bool) -> Self {
         let low = cpu.read_next_byte() as Word;
         let high = cpu.read_next_byte() as Word;
-        let address = (high << 8 | low).wrapping_add(cpu.registers.x as Word);
-        
-        if page_boundary {
-            if (address & 0xFF00) >> 8 != high as Word {
-                cpu.cycles += 1;
-            }
-        }
+        let base = high << 8 | low;
+        let address = base.wrapping_add(cpu.registers.x as Word);
+        cpu.index_penalty(base, address, page_boundary);
 
         Self {
             address,
@@ -190,8 +211,8 @@ impl<M: Memory> Addressing<M> for AbsoluteX {
     }
 }
 
-impl<M: Memory> ReadMode<M> for AbsoluteX {}
-impl<M: Memory> WriteMode<M> for AbsoluteX {}
+impl<B: CpuBus> ReadMode<B> for AbsoluteX {}
+impl<B: CpuBus> WriteMode<B> for AbsoluteX {}
 
 // ABSOLUTE_Y
 
@@ -199,17 +220,13 @@ pub struct AbsoluteY {
     address: Address,
 }
 
-impl<M: Memory> Addressing<M> for AbsoluteY {
-    fn create_addressing(cpu: &mut CPU<M>, page_boundary: bool) -> Self {
+impl<B: CpuBus> Addressing<B> for AbsoluteY {
+    fn create_addressing(cpu: &mut CPU<B>, page_boundary: bool) -> Self {
         let low = cpu.read_next_byte() as Word;
         let high = cpu.read_next_byte() as Word;
-        let address = (high << 8 | low).wrapping_add(cpu.registers.y as Word);
-        
-        if page_boundary {
-            if (address & 0xFF00) >> 8 != high as Word {
-                cpu.cycles += 1;
-            }
-        }
+        let base = high << 8 | low;
+        let address = base.wrapping_add(cpu.registers.y as Word);
+        cpu.index_penalty(base, address, page_boundary);
 
         Self {
             address,
@@ -221,8 +238,8 @@ impl<M: Memory> Addressing<M> for AbsoluteY {
     }
 }
 
-impl<M: Memory> ReadMode<M> for AbsoluteY {}
-impl<M: Memory> WriteMode<M> for AbsoluteY {}
+impl<B: CpuBus> ReadMode<B> for AbsoluteY {}
+impl<B: CpuBus> WriteMode<B> for AbsoluteY {}
 
 // INDEXED_INDIRECT_X
 
@@ -230,10 +247,11 @@ pub struct IndexedIndirectX {
     address: Address,
 }
 
-impl<M: Memory> Addressing<M> for IndexedIndirectX {
-    fn create_addressing(cpu: &mut CPU<M>, _page_boundary: bool) -> Self {
-        let value = cpu.read_next_byte() + cpu.registers.x;
-        let address = cpu.read_word_zero_page(value);
+impl<B: CpuBus> Addressing<B> for IndexedIndirectX {
+    fn create_addressing(cpu: &mut CPU<B>, _page_boundary: bool) -> Self {
+        let base = cpu.read_next_byte();
+        cpu.dummy_read(base as Word);
+        let address = cpu.read_word_zero_page(base.wrapping_add(cpu.registers.x));
         Self { address }
     }
 
@@ -242,8 +260,8 @@ impl<M: Memory> Addressing<M> for IndexedIndirectX {
     }
 }
 
-impl<M: Memory> ReadMode<M> for IndexedIndirectX {}
-impl<M: Memory> WriteMode<M> for IndexedIndirectX {}
+impl<B: CpuBus> ReadMode<B> for IndexedIndirectX {}
+impl<B: CpuBus> WriteMode<B> for IndexedIndirectX {}
 
 // INDIRECT_INDEXED_Y
 
@@ -251,26 +269,36 @@ pub struct IndirectIndexedY {
     address: Address,
 }
 
-impl<M: Memory> Addressing<M> for IndirectIndexedY {
-    fn create_addressing(cpu: &mut CPU<M>, page_boundary: bool) -> Self {
-        let address = cpu.read_next_byte() as Word;
+impl<B: CpuBus> Addressing<B> for IndirectIndexedY {
+    fn create_addressing(cpu: &mut CPU<B>, page_boundary: bool) -> Self {
+        let pointer = cpu.read_next_byte();
+        let base = cpu.read_word_zero_page(pointer);
+        let address = base.wrapping_add(cpu.registers.y as Word);
+        cpu.index_penalty(base, address, page_boundary);
 
-        let low = cpu.read_byte(address) as Word;
+        Self { address }
+    }
 
-        let high = if address < 0xFF {
-            cpu.read_byte(address + 1)
-        } else {
-            cpu.read_byte(0x0000)
-        } as Word;
+    fn get_address(&self) -> Option<Address> {
+        Some(self.address)
+    }
+}
 
-        let address = (high << 8 | low).wrapping_add(cpu.registers.y as Word);
-        if page_boundary {
-            if (address & 0xFF00) >> 8 != high as Word {
-                cpu.cycles += 1;
-            }
-        }
+impl<B: CpuBus> ReadMode<B> for IndirectIndexedY {}
+impl<B: CpuBus> WriteMode<B> for IndirectIndexedY {}
 
-        Self { address }
+// ZERO_PAGE_INDIRECT (65C02 only)
+
+pub struct ZeroPageIndirect {
+    address: Address,
+}
+
+impl<B: CpuBus> Addressing<B> for ZeroPageIndirect {
+    fn create_addressing(cpu: &mut CPU<B>, _page_boundary: bool) -> Self {
+        let pointer = cpu.read_next_byte();
+        Self {
+            address: cpu.read_word_zero_page(pointer),
+        }
     }
 
     fn get_address(&self) -> Option<Address> {
@@ -278,5 +306,5 @@ impl<M: Memory> Addressing<M> for IndirectIndexedY {
     }
 }
 
-impl<M: Memory> ReadMode<M> for IndirectIndexedY {}
-impl<M: Memory> WriteMode<M> for IndirectIndexedY {}
+impl<B: CpuBus> ReadMode<B> for ZeroPageIndirect {}
+impl<B: CpuBus> WriteMode<B> for ZeroPageIndirect {}
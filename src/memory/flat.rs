@@ -0,0 +1,68 @@
+use crate::bus::CpuBus;
+use crate::memory::Address;
+use crate::memory::Byte;
+use crate::memory::Memory;
+use std::ops::{Deref, DerefMut};
+
+pub const FLAT_MEMORY_SIZE: usize = 0x10000;
+
+/// 64KB of RAM covering the whole address space, with nothing else on the
+/// bus. Enough to run a bare 6502 (e.g. test suites) without a NES around it.
+pub struct FlatMemory {
+    pub bytes: Box<[u8; FLAT_MEMORY_SIZE]>,
+    /// The IRQ line, which only changes when set by hand.
+    pub irq: bool,
+}
+
+impl FlatMemory {
+    pub fn new() -> Self {
+        Self {
+            bytes: Box::new([0; FLAT_MEMORY_SIZE]),
+            irq: false,
+        }
+    }
+
+    /// Copies `data` to memory starting at `address`, wrapping around at $FFFF.
+    pub fn load(&mut self, address: Address, data: &[Byte]) {
+        for (offset, &byte) in data.iter().enumerate() {
+            self.bytes[(address as usize + offset) % FLAT_MEMORY_SIZE] = byte;
+        }
+    }
+}
+
+impl Default for FlatMemory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for FlatMemory {
+    type Target = [u8; FLAT_MEMORY_SIZE];
+
+    fn deref(&self) -> &[u8; FLAT_MEMORY_SIZE] {
+        &self.bytes
+    }
+}
+
+impl DerefMut for FlatMemory {
+    fn deref_mut(&mut self) -> &mut [u8; FLAT_MEMORY_SIZE] {
+        &mut self.bytes
+    }
+}
+
+impl Memory for FlatMemory {
+    fn read_byte(&mut self, address: Address) -> Byte {
+        self.bytes[address as usize]
+    }
+
+    fn write_byte(&mut self, address: Address, value: Byte) {
+        self.bytes[address as usize] = value;
+    }
+}
+
+/// No clock and no NMI source, so only the IRQ line differs from the defaults.
+impl CpuBus for FlatMemory {
+    fn irq(&self) -> bool {
+        self.irq
+    }
+}
@@ -1,4 +1,5 @@
 pub mod addressing;
+pub mod flat;
 pub mod ram;
 
 pub type Byte = u8;
@@ -12,24 +13,27 @@ pub type ZeroPageAddress = Byte;
 
 pub trait Memory {
     fn read_byte(&mut self, address: Address) -> Byte;
+    /// Reads a little endian word. The high byte wraps around to $0000.
     fn read_word(&mut self, address: Address) -> Word {
         let low = self.read_byte(address) as Word;
-        let high = self.read_byte(address + 1) as Word;
+        let high = self.read_byte(address.wrapping_add(1)) as Word;
 
         (high << 8) | low
     }
 
     fn write_byte(&mut self, address: Address, value: Byte);
     fn write_word(&mut self, address: Address, value: Word) {
-        self.write_byte(address, (value >> 8) as Byte);
-        self.write_byte(address + 1, (value & 0xff) as Byte)
+        self.write_byte(address, (value & 0xff) as Byte);
+        self.write_byte(address.wrapping_add(1), (value >> 8) as Byte)
     }
 }
 
 pub trait ZeroPageMemory: Memory {
+    /// Reads a pointer from the zero page. A pointer at $FF takes its high
+    /// byte from $00, not $0100.
     fn read_word_zero_page(&mut self, address: ZeroPageAddress) -> Word {
         let low = self.read_byte(address as Word) as Word;
-        let high = self.read_byte((address + 1) as Word) as Word;
+        let high = self.read_byte(address.wrapping_add(1) as Word) as Word;
 
         (high << 8) | low
     }
@@ -13,6 +13,12 @@ impl RAM {
     }
 }
 
+impl Default for RAM {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Deref for RAM {
     type Target = [u8; 0x800];
 
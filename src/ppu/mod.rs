@@ -0,0 +1,250 @@
+use crate::{
+    cartridge::Cartridge,
+    memory::{Address, Byte},
+};
+
+use self::{nametable::Nametables, registers::Registers, render::Sprite};
+
+pub mod nametable;
+pub mod palette;
+pub mod registers;
+pub mod render;
+
+pub const SCREEN_WIDTH: usize = 256;
+pub const SCREEN_HEIGHT: usize = 240;
+
+pub const DOTS_PER_SCANLINE: usize = 341;
+pub const SCANLINES_PER_FRAME: usize = 262;
+
+pub const VISIBLE_SCANLINES: usize = 240;
+pub const VBLANK_SCANLINE: usize = 241;
+pub const PRE_RENDER_SCANLINE: usize = 261;
+
+pub struct PPU {
+    pub registers: Registers,
+    pub oam: [Byte; 0x100],
+    pub nametables: Nametables,
+    pub palette: [Byte; 0x20],
+    pub dot: usize,
+    pub scanline: usize,
+    pub frames: usize,
+    framebuffer: Vec<Byte>,
+    sprites: Vec<Sprite>,
+    sprite_zero_hit: Option<usize>,
+    frame_ready: bool,
+    data_buffer: Byte,
+    io_latch: Byte,
+    nmi: bool,
+}
+
+impl PPU {
+    pub fn new() -> Self {
+        Self {
+            registers: Registers::new(),
+            oam: [0; 0x100],
+            nametables: Nametables::new(),
+            palette: [0; 0x20],
+            dot: 0,
+            scanline: 0,
+            frames: 0,
+            framebuffer: vec![0; SCREEN_WIDTH * SCREEN_HEIGHT * 3],
+            sprites: Vec::with_capacity(render::MAX_SPRITES_PER_SCANLINE),
+            sprite_zero_hit: None,
+            frame_ready: false,
+            data_buffer: 0,
+            io_latch: 0,
+            nmi: false,
+        }
+    }
+
+    /// The last rendered frame, as 256x240 packed RGB pixels.
+    pub fn frame(&self) -> &[Byte] {
+        &self.framebuffer
+    }
+
+    /// Returns `true` once every time a frame has been fully rendered.
+    pub fn poll_frame(&mut self) -> bool {
+        std::mem::take(&mut self.frame_ready)
+    }
+
+    /// Returns `true` once for every NMI raised by the PPU.
+    pub fn poll_nmi(&mut self) -> bool {
+        std::mem::take(&mut self.nmi)
+    }
+
+    /// Reads one of the eight registers mirrored every 8 bytes in $2000-$3FFF.
+    pub fn read_register(&mut self, address: Address, cartridge: &mut Cartridge) -> Byte {
+        match address & 0x0007 {
+            0x0002 => {
+                let value = (self.registers.status & 0xE0) | (self.io_latch & 0x1F);
+                self.registers.set_status(Registers::STATUS_VBLANK, false);
+                self.registers.w = false;
+                self.io_latch = value;
+            }
+            0x0004 => {
+                self.io_latch = self.oam[self.registers.oam_address as usize];
+            }
+            0x0007 => {
+                let address = self.registers.v & 0x3FFF;
+                let value = self.read_vram(address, cartridge);
+                if address >= 0x3F00 {
+                    // Palette reads are not buffered, but still refill the buffer
+                    // with the nametable byte "underneath" the palette.
+                    self.data_buffer = self.read_vram(address - 0x1000, cartridge);
+                    self.io_latch = (self.io_latch & 0xC0) | (value & 0x3F);
+                } else {
+                    self.io_latch = self.data_buffer;
+                    self.data_buffer = value;
+                }
+                self.increment_vram_address();
+            }
+            _ => {} // Write-only registers return the open bus.
+        }
+
+        self.io_latch
+    }
+
+    pub fn write_register(&mut self, address: Address, value: Byte, cartridge: &mut Cartridge) {
+        self.io_latch = value;
+
+        match address & 0x0007 {
+            0x0000 => {
+                let nmi_enabled = self.registers.get_ctrl(Registers::CTRL_NMI);
+                self.registers.write_ctrl(value);
+                if !nmi_enabled
+                    && self.registers.get_ctrl(Registers::CTRL_NMI)
+                    && self.registers.get_status(Registers::STATUS_VBLANK)
+                {
+                    self.nmi = true;
+                }
+            }
+            0x0001 => self.registers.mask = value,
+            0x0002 => {}
+            0x0003 => self.registers.oam_address = value,
+            0x0004 => {
+                self.oam[self.registers.oam_address as usize] = value;
+                self.registers.oam_address = self.registers.oam_address.wrapping_add(1);
+            }
+            0x0005 => self.registers.write_scroll(value),
+            0x0006 => self.registers.write_address(value),
+            0x0007 => {
+                self.write_vram(self.registers.v & 0x3FFF, value, cartridge);
+                self.increment_vram_address();
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn increment_vram_address(&mut self) {
+        self.registers.v = self.registers.v.wrapping_add(self.registers.vram_increment()) & 0x7FFF;
+    }
+
+    pub fn read_vram(&mut self, address: Address, cartridge: &mut Cartridge) -> Byte {
+        match address {
+            0x0000..=0x1FFF => cartridge.read_chr(address),
+            0x2000..=0x3EFF => self.nametables.read(address, cartridge),
+            _ => self.palette[palette_index(address)],
+        }
+    }
+
+    pub fn write_vram(&mut self, address: Address, value: Byte, cartridge: &mut Cartridge) {
+        match address {
+            0x0000..=0x1FFF => cartridge.write_chr(address, value),
+            0x2000..=0x3EFF => self.nametables.write(address, value, cartridge),
+            _ => self.palette[palette_index(address)] = value,
+        }
+    }
+
+    /// Advances the PPU by the given amount of dots (3 per CPU cycle).
+    pub fn tick(&mut self, dots: usize, cartridge: &mut Cartridge) {
+        for _ in 0..dots {
+            self.tick_dot(cartridge);
+        }
+    }
+
+    fn tick_dot(&mut self, cartridge: &mut Cartridge) {
+        cartridge.ppu_tick();
+
+        let rendering = self.registers.rendering_enabled();
+        let visible = self.scanline < VISIBLE_SCANLINES;
+
+        if visible && self.dot == 1 {
+            self.sprite_zero_hit = None;
+            self.render_scanline(cartridge);
+        }
+
+        if let Some(x) = self.sprite_zero_hit {
+            if visible && self.dot == x + 2 {
+                self.registers.set_status(Registers::STATUS_SPRITE_ZERO_HIT, true);
+            }
+        }
+
+        if rendering && (visible || self.scanline == PRE_RENDER_SCANLINE) {
+            match self.dot {
+                256 => self.registers.increment_y(),
+                257 => {
+                    self.registers.copy_x();
+                    self.evaluate_sprites(cartridge);
+                }
+                280..=304 if self.scanline == PRE_RENDER_SCANLINE => self.registers.copy_y(),
+                _ => {}
+            }
+        }
+
+        match (self.scanline, self.dot) {
+            (VBLANK_SCANLINE, 1) => {
+                self.frame_ready = true;
+                self.registers.set_status(Registers::STATUS_VBLANK, true);
+                if self.registers.get_ctrl(Registers::CTRL_NMI) {
+                    self.nmi = true;
+                }
+            }
+            (PRE_RENDER_SCANLINE, 1) => {
+                self.registers.set_status(
+                    Registers::STATUS_VBLANK
+                        | Registers::STATUS_SPRITE_ZERO_HIT
+                        | Registers::STATUS_SPRITE_OVERFLOW,
+                    false,
+                );
+            }
+            _ => {}
+        }
+
+        self.dot += 1;
+
+        // The pre-render scanline is one dot shorter on odd frames when rendering.
+        if self.scanline == PRE_RENDER_SCANLINE
+            && self.dot == DOTS_PER_SCANLINE - 1
+            && self.frames % 2 == 1
+            && self.registers.rendering_enabled()
+        {
+            self.dot = DOTS_PER_SCANLINE;
+        }
+
+        if self.dot == DOTS_PER_SCANLINE {
+            self.dot = 0;
+            self.scanline += 1;
+
+            if self.scanline == SCANLINES_PER_FRAME {
+                self.scanline = 0;
+                self.frames += 1;
+            }
+        }
+    }
+}
+
+impl Default for PPU {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn palette_index(address: Address) -> usize {
+    let index = address as usize & 0x1F;
+    // $3F10/$3F14/$3F18/$3F1C mirror the background entries.
+    if index & 0x13 == 0x10 {
+        index & 0x0F
+    } else {
+        index
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{
+    cartridge::{Cartridge, Mirroring},
+    memory::{Address, Byte},
+};
+
+pub const CIRAM_SIZE: usize = 0x800;
+pub const FOUR_SCREEN_VRAM_SIZE: usize = 0x1000;
+
+/// The console's 2KB of nametable RAM (CIRAM). The cartridge decides how the
+/// four logical nametables at $2000-$2FFF fold onto its two physical pages,
+/// or supplies its own 4KB of VRAM for four-screen boards.
+pub struct Nametables {
+    pub ciram: [Byte; CIRAM_SIZE],
+}
+
+impl Nametables {
+    pub fn new() -> Self {
+        Self {
+            ciram: [0; CIRAM_SIZE],
+        }
+    }
+
+    /// Reads $2000-$3EFF, where $3000-$3EFF mirrors $2000-$2EFF.
+    pub fn read(&self, address: Address, cartridge: &Cartridge) -> Byte {
+        match cartridge.mirroring() {
+            Mirroring::FourScreen => cartridge.vram[address as usize % FOUR_SCREEN_VRAM_SIZE],
+            mirroring => self.ciram[ciram_index(address, mirroring)],
+        }
+    }
+
+    pub fn write(&mut self, address: Address, value: Byte, cartridge: &mut Cartridge) {
+        match cartridge.mirroring() {
+            Mirroring::FourScreen => {
+                cartridge.vram[address as usize % FOUR_SCREEN_VRAM_SIZE] = value
+            }
+            mirroring => self.ciram[ciram_index(address, mirroring)] = value,
+        }
+    }
+}
+
+impl Default for Nametables {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Maps a nametable address onto one of the two 1KB CIRAM pages.
+fn ciram_index(address: Address, mirroring: Mirroring) -> usize {
+    let address = address as usize;
+    let offset = address & 0x03FF;
+    match mirroring {
+        // $2000/$2400 share page A, $2800/$2C00 page B.
+        Mirroring::Horizontal => (address & 0x0800) >> 1 | offset,
+        // $2000/$2800 share page A, $2400/$2C00 page B.
+        Mirroring::Vertical => address & 0x07FF,
+        Mirroring::SingleScreenA => offset,
+        Mirroring::SingleScreenB => 0x0400 | offset,
+        Mirroring::FourScreen => unreachable!(),
+    }
+}
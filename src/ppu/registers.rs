@@ -0,0 +1,153 @@
+use crate::memory::{Byte, Word};
+
+pub struct Registers {
+    pub ctrl: Byte,        // PPUCTRL ($2000)
+    pub mask: Byte,        // PPUMASK ($2001)
+    pub status: Byte,      // PPUSTATUS ($2002)
+    pub oam_address: Byte, // OAMADDR ($2003)
+    pub v: Word,           // Current VRAM address (15 bits)
+    pub t: Word,           // Temporary VRAM address (15 bits)
+    pub x: Byte,           // Fine X scroll (3 bits)
+    pub w: bool,           // First or second write toggle
+}
+
+impl Registers {
+    pub const CTRL_NAMETABLE: u8 = 0b11;
+    pub const CTRL_INCREMENT: u8 = 1 << 2;
+    pub const CTRL_SPRITE_TABLE: u8 = 1 << 3;
+    pub const CTRL_BACKGROUND_TABLE: u8 = 1 << 4;
+    pub const CTRL_SPRITE_SIZE: u8 = 1 << 5;
+    pub const CTRL_MASTER_SLAVE: u8 = 1 << 6;
+    pub const CTRL_NMI: u8 = 1 << 7;
+
+    pub const MASK_GRAYSCALE: u8 = 1 << 0;
+    pub const MASK_BACKGROUND_LEFT: u8 = 1 << 1;
+    pub const MASK_SPRITES_LEFT: u8 = 1 << 2;
+    pub const MASK_BACKGROUND: u8 = 1 << 3;
+    pub const MASK_SPRITES: u8 = 1 << 4;
+    pub const MASK_EMPHASIZE_RED: u8 = 1 << 5;
+    pub const MASK_EMPHASIZE_GREEN: u8 = 1 << 6;
+    pub const MASK_EMPHASIZE_BLUE: u8 = 1 << 7;
+
+    pub const STATUS_SPRITE_OVERFLOW: u8 = 1 << 5;
+    pub const STATUS_SPRITE_ZERO_HIT: u8 = 1 << 6;
+    pub const STATUS_VBLANK: u8 = 1 << 7;
+
+    pub fn new() -> Self {
+        Self {
+            ctrl: 0x00,
+            mask: 0x00,
+            status: 0x00,
+            oam_address: 0x00,
+            v: 0x0000,
+            t: 0x0000,
+            x: 0x00,
+            w: false,
+        }
+    }
+
+    pub fn get_ctrl(&self, flag: u8) -> bool {
+        (self.ctrl & flag) != 0
+    }
+
+    pub fn get_mask(&self, flag: u8) -> bool {
+        (self.mask & flag) != 0
+    }
+
+    pub fn get_status(&self, flag: u8) -> bool {
+        (self.status & flag) != 0
+    }
+
+    pub fn set_status(&mut self, flag: u8, on: bool) {
+        if on {
+            self.status |= flag;
+        } else {
+            self.status &= !flag;
+        }
+    }
+
+    pub fn rendering_enabled(&self) -> bool {
+        self.get_mask(Registers::MASK_BACKGROUND) || self.get_mask(Registers::MASK_SPRITES)
+    }
+
+    pub fn vram_increment(&self) -> Word {
+        if self.get_ctrl(Registers::CTRL_INCREMENT) {
+            32
+        } else {
+            1
+        }
+    }
+
+    pub fn write_ctrl(&mut self, value: Byte) {
+        self.ctrl = value;
+        // t: ...GH.. ........ <- d: ......GH
+        self.t = (self.t & 0xF3FF) | ((value as Word & 0x03) << 10);
+    }
+
+    pub fn write_scroll(&mut self, value: Byte) {
+        if !self.w {
+            // t: ....... ...ABCDE <- d: ABCDE...
+            self.t = (self.t & 0xFFE0) | (value as Word >> 3);
+            self.x = value & 0x07;
+        } else {
+            // t: FGH..AB CDE..... <- d: ABCDEFGH
+            self.t = (self.t & 0x8C1F) | ((value as Word & 0x07) << 12) | ((value as Word & 0xF8) << 2);
+        }
+        self.w = !self.w;
+    }
+
+    pub fn write_address(&mut self, value: Byte) {
+        if !self.w {
+            // t: .CDEFGH ........ <- d: ..CDEFGH, bit 14 is cleared.
+            self.t = (self.t & 0x00FF) | ((value as Word & 0x3F) << 8);
+        } else {
+            self.t = (self.t & 0xFF00) | value as Word;
+            self.v = self.t;
+        }
+        self.w = !self.w;
+    }
+
+    pub fn increment_x(&mut self) {
+        if self.v & 0x001F == 31 {
+            self.v &= !0x001F;
+            self.v ^= 0x0400; // Switch horizontal nametable.
+        } else {
+            self.v += 1;
+        }
+    }
+
+    pub fn increment_y(&mut self) {
+        if self.v & 0x7000 != 0x7000 {
+            self.v += 0x1000; // Fine Y.
+            return;
+        }
+
+        self.v &= !0x7000;
+        let mut coarse_y = (self.v & 0x03E0) >> 5;
+        if coarse_y == 29 {
+            coarse_y = 0;
+            self.v ^= 0x0800; // Switch vertical nametable.
+        } else if coarse_y == 31 {
+            coarse_y = 0; // Attribute rows wrap without switching.
+        } else {
+            coarse_y += 1;
+        }
+        self.v = (self.v & !0x03E0) | (coarse_y << 5);
+    }
+
+    pub fn copy_x(&mut self) {
+        // v: ....A.. ...BCDEF <- t: ....A.. ...BCDEF
+        self.v = (self.v & !0x041F) | (self.t & 0x041F);
+    }
+
+    pub fn copy_y(&mut self) {
+        // v: GHIA.BC DEF..... <- t: GHIA.BC DEF.....
+        self.v = (self.v & !0x7BE0) | (self.t & 0x7BE0);
+    }
+}
+
+impl Default for Registers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
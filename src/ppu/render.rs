@@ -0,0 +1,229 @@
+use crate::{
+    cartridge::Cartridge,
+    memory::{Address, Byte, Word},
+};
+
+use super::{palette::PALETTE, registers::Registers, PPU, SCREEN_WIDTH};
+
+pub const MAX_SPRITES_PER_SCANLINE: usize = 8;
+
+pub struct Sprite {
+    pub x: Byte,
+    pub attributes: Byte,
+    pub low: Byte,
+    pub high: Byte,
+    pub zero: bool,
+}
+
+impl Sprite {
+    pub const PALETTE: u8 = 0b11;
+    pub const BEHIND_BACKGROUND: u8 = 1 << 5;
+    pub const FLIP_HORIZONTAL: u8 = 1 << 6;
+    pub const FLIP_VERTICAL: u8 = 1 << 7;
+
+    fn pixel(&self, x: usize) -> Byte {
+        let column = x.wrapping_sub(self.x as usize);
+        if column >= 8 {
+            return 0;
+        }
+
+        let bit = 7 - column;
+        ((self.high >> bit) & 0x01) << 1 | ((self.low >> bit) & 0x01)
+    }
+}
+
+impl PPU {
+    pub(super) fn sprite_height(&self) -> usize {
+        if self.registers.get_ctrl(Registers::CTRL_SPRITE_SIZE) {
+            16
+        } else {
+            8
+        }
+    }
+
+    fn read_pattern(&mut self, address: Address, cartridge: &mut Cartridge) -> (Byte, Byte) {
+        let low = self.read_vram(address, cartridge);
+        let high = self.read_vram(address + 8, cartridge);
+        (low, high)
+    }
+
+    /// Draws the current visible scanline into the framebuffer, using the
+    /// scroll position held in `v` and the sprites evaluated on the previous line.
+    pub(super) fn render_scanline(&mut self, cartridge: &mut Cartridge) {
+        let mut line = [0 as Byte; SCREEN_WIDTH];
+
+        if self.registers.rendering_enabled() {
+            let background = self.fetch_background(cartridge);
+            let show_background = self.registers.get_mask(Registers::MASK_BACKGROUND);
+            let show_sprites = self.registers.get_mask(Registers::MASK_SPRITES);
+            let show_background_left = self.registers.get_mask(Registers::MASK_BACKGROUND_LEFT);
+            let show_sprites_left = self.registers.get_mask(Registers::MASK_SPRITES_LEFT);
+
+            for (x, color) in line.iter_mut().enumerate() {
+                let left = x < 8;
+
+                let (mut bg_pixel, bg_palette) = background[x];
+                if !show_background || (left && !show_background_left) {
+                    bg_pixel = 0;
+                }
+
+                let mut sprite = None;
+                if show_sprites && (!left || show_sprites_left) {
+                    for s in self.sprites.iter() {
+                        let pixel = s.pixel(x);
+                        if pixel == 0 {
+                            continue;
+                        }
+
+                        if s.zero && bg_pixel != 0 && x != 255 && self.sprite_zero_hit.is_none() {
+                            self.sprite_zero_hit = Some(x);
+                        }
+
+                        if sprite.is_none() {
+                            sprite = Some((pixel, s.attributes));
+                        }
+                    }
+                }
+
+                *color = match sprite {
+                    Some((pixel, attributes))
+                        if bg_pixel == 0 || attributes & Sprite::BEHIND_BACKGROUND == 0 =>
+                    {
+                        self.palette[0x10 | ((attributes & Sprite::PALETTE) << 2 | pixel) as usize]
+                    }
+                    _ if bg_pixel != 0 => self.palette[(bg_palette << 2 | bg_pixel) as usize],
+                    _ => self.palette[0],
+                };
+            }
+        } else {
+            line = [self.palette[0]; SCREEN_WIDTH];
+        }
+
+        let offset = self.scanline * SCREEN_WIDTH * 3;
+        for (x, color) in line.iter().enumerate() {
+            let mut color = *color & 0x3F;
+            if self.registers.get_mask(Registers::MASK_GRAYSCALE) {
+                color &= 0x30;
+            }
+
+            let (r, g, b) = PALETTE[color as usize];
+            self.framebuffer[offset + x * 3..offset + x * 3 + 3].copy_from_slice(&[r, g, b]);
+        }
+    }
+
+    /// Fetches the 33 tiles overlapping the scanline and returns the
+    /// (pixel, palette) pair for every visible dot after fine X scrolling.
+    fn fetch_background(&mut self, cartridge: &mut Cartridge) -> [(Byte, Byte); SCREEN_WIDTH] {
+        let mut pixels = [(0, 0); SCREEN_WIDTH];
+        let table: Word = if self.registers.get_ctrl(Registers::CTRL_BACKGROUND_TABLE) {
+            0x1000
+        } else {
+            0x0000
+        };
+
+        let v = self.registers.v;
+        let fine_y = (v >> 12) & 0x07;
+        let fine_x = self.registers.x as usize;
+        let mut tile_address = v & 0x0FFF;
+
+        for tile in 0..33 {
+            let index = self.read_vram(0x2000 | tile_address, cartridge) as Word;
+
+            let attribute_address =
+                0x23C0 | (tile_address & 0x0C00) | ((tile_address >> 4) & 0x38) | ((tile_address >> 2) & 0x07);
+            let attribute = self.read_vram(attribute_address, cartridge);
+            let shift = ((tile_address >> 4) & 0x04) | (tile_address & 0x02);
+            let palette = (attribute >> shift) & 0x03;
+
+            let (low, high) = self.read_pattern(table + index * 16 + fine_y, cartridge);
+
+            for column in 0..8 {
+                let x = (tile * 8 + column) as isize - fine_x as isize;
+                if !(0..SCREEN_WIDTH as isize).contains(&x) {
+                    continue;
+                }
+
+                let bit = 7 - column;
+                let pixel = ((high >> bit) & 0x01) << 1 | ((low >> bit) & 0x01);
+                pixels[x as usize] = (pixel, palette);
+            }
+
+            // Increment coarse X, switching horizontal nametable on wrap.
+            if tile_address & 0x001F == 31 {
+                tile_address = (tile_address & !0x001F) ^ 0x0400;
+            } else {
+                tile_address += 1;
+            }
+        }
+
+        pixels
+    }
+
+    /// Selects the (up to 8) sprites on the next scanline and fetches their
+    /// pattern data. Empty slots still fetch tile $FF, like the hardware does.
+    pub(super) fn evaluate_sprites(&mut self, cartridge: &mut Cartridge) {
+        self.sprites.clear();
+
+        let height = self.sprite_height();
+
+        if self.scanline < super::VISIBLE_SCANLINES {
+            for index in 0..64 {
+                let y = self.oam[index * 4] as usize;
+                let row = self.scanline.wrapping_sub(y);
+                if row >= height {
+                    continue;
+                }
+
+                if self.sprites.len() == MAX_SPRITES_PER_SCANLINE {
+                    self.registers.set_status(Registers::STATUS_SPRITE_OVERFLOW, true);
+                    break;
+                }
+
+                let tile = self.oam[index * 4 + 1];
+                let attributes = self.oam[index * 4 + 2];
+                let x = self.oam[index * 4 + 3];
+
+                let row = if attributes & Sprite::FLIP_VERTICAL != 0 {
+                    height - 1 - row
+                } else {
+                    row
+                };
+
+                let (low, high) = self.read_pattern(self.sprite_pattern_address(tile, row), cartridge);
+                let (low, high) = if attributes & Sprite::FLIP_HORIZONTAL != 0 {
+                    (low.reverse_bits(), high.reverse_bits())
+                } else {
+                    (low, high)
+                };
+
+                self.sprites.push(Sprite {
+                    x,
+                    attributes,
+                    low,
+                    high,
+                    zero: index == 0,
+                });
+            }
+        }
+
+        for _ in self.sprites.len()..MAX_SPRITES_PER_SCANLINE {
+            self.read_pattern(self.sprite_pattern_address(0xFF, 0), cartridge);
+        }
+    }
+
+    fn sprite_pattern_address(&self, tile: Byte, row: usize) -> Address {
+        let row = row as Word;
+        if self.sprite_height() == 16 {
+            let table = (tile as Word & 0x01) * 0x1000;
+            let tile = (tile & 0xFE) as Word + row / 8;
+            table + tile * 16 + (row & 0x07)
+        } else {
+            let table = if self.registers.get_ctrl(Registers::CTRL_SPRITE_TABLE) {
+                0x1000
+            } else {
+                0x0000
+            };
+            table + tile as Word * 16 + row
+        }
+    }
+}
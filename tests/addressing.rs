@@ -0,0 +1,172 @@
+use famines::{
+    cpu::CPU,
+    memory::{flat::FlatMemory, Address, Byte, Memory},
+};
+
+fn cpu_at(pc: Address, program: &[Byte]) -> CPU<FlatMemory> {
+    let mut cpu = CPU::new(FlatMemory::new());
+    for (offset, &byte) in program.iter().enumerate() {
+        cpu.bus[pc.wrapping_add(offset as Address) as usize] = byte;
+    }
+    cpu.registers.pc = pc;
+    cpu
+}
+
+#[test]
+fn zero_page_x_wraps_within_zero_page() {
+    // LDA $F0,X
+    let mut cpu = cpu_at(0x0400, &[0xB5, 0xF0]);
+    cpu.registers.x = 0x20;
+    cpu.bus[0x0010] = 0x11;
+    cpu.bus[0x0110] = 0x22;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.a, 0x11);
+}
+
+#[test]
+fn zero_page_y_wraps_within_zero_page() {
+    // LDX $FF,Y
+    let mut cpu = cpu_at(0x0400, &[0xB6, 0xFF]);
+    cpu.registers.y = 0x01;
+    cpu.bus[0x0000] = 0x33;
+    cpu.bus[0x0100] = 0x44;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.x, 0x33);
+}
+
+#[test]
+fn absolute_indexed_wraps_at_end_of_memory() {
+    // LDA $FFF0,X then LDA $FFFF,Y
+    let mut cpu = cpu_at(0x0400, &[0xBD, 0xF0, 0xFF, 0xB9, 0xFF, 0xFF]);
+    cpu.registers.x = 0x20;
+    cpu.registers.y = 0x02;
+    cpu.bus[0x0010] = 0x55;
+    cpu.bus[0x0001] = 0x66;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.a, 0x55);
+    assert_eq!(cpu.cycles, 5); // The wrap counts as a page crossing.
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.a, 0x66);
+}
+
+#[test]
+fn indexed_indirect_x_wraps_pointer_within_zero_page() {
+    // LDA ($80,X) with $80 + X overflowing to $10
+    let mut cpu = cpu_at(0x0400, &[0xA1, 0x80]);
+    cpu.registers.x = 0x90;
+    cpu.bus[0x0010] = 0x34;
+    cpu.bus[0x0011] = 0x12;
+    cpu.bus[0x1234] = 0x77;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.a, 0x77);
+
+    // LDA ($FF,X) takes the high byte of the pointer from $00.
+    let mut cpu = cpu_at(0x0400, &[0xA1, 0xFF]);
+    cpu.bus[0x00FF] = 0x78;
+    cpu.bus[0x0000] = 0x56;
+    cpu.bus[0x0100] = 0x9A;
+    cpu.bus[0x5678] = 0x88;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.a, 0x88);
+}
+
+#[test]
+fn indirect_indexed_y_wraps_pointer_and_address() {
+    // LDA ($FF),Y takes the high byte of the pointer from $00.
+    let mut cpu = cpu_at(0x0400, &[0xB1, 0xFF]);
+    cpu.registers.y = 0x01;
+    cpu.bus[0x00FF] = 0x00;
+    cpu.bus[0x0000] = 0x30;
+    cpu.bus[0x0100] = 0x40;
+    cpu.bus[0x3001] = 0x99;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.a, 0x99);
+
+    // LDA ($10),Y with ($10) = $FFFF wraps around to $0000 + Y - 1.
+    let mut cpu = cpu_at(0x0400, &[0xB1, 0x10]);
+    cpu.registers.y = 0x03;
+    cpu.bus[0x0010] = 0xFF;
+    cpu.bus[0x0011] = 0xFF;
+    cpu.bus[0x0002] = 0xAB;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.a, 0xAB);
+}
+
+#[test]
+fn indirect_jump_does_not_cross_pages() {
+    // JMP ($10FF) reads the high byte from $1000, not $1100.
+    let mut cpu = cpu_at(0x0400, &[0x6C, 0xFF, 0x10]);
+    cpu.bus[0x10FF] = 0x34;
+    cpu.bus[0x1000] = 0x12;
+    cpu.bus[0x1100] = 0x56;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.pc, 0x1234);
+
+    // JMP ($FFFF) stays on page $FF as well.
+    let mut cpu = cpu_at(0x0400, &[0x6C, 0xFF, 0xFF]);
+    cpu.bus[0xFFFF] = 0x78;
+    cpu.bus[0xFF00] = 0x56;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.pc, 0x5678);
+}
+
+#[test]
+fn immediate_and_absolute_operands_wrap_program_counter() {
+    // LDA #$42 at $FFFF, with the operand at $0000.
+    let mut cpu = cpu_at(0xFFFF, &[0xA9, 0x42]);
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.a, 0x42);
+    assert_eq!(cpu.registers.pc, 0x0001);
+
+    // LDA $1234 at $FFFE.
+    let mut cpu = cpu_at(0xFFFE, &[0xAD, 0x34, 0x12]);
+    cpu.bus[0x1234] = 0x24;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.a, 0x24);
+    assert_eq!(cpu.registers.pc, 0x0001);
+}
+
+#[test]
+fn stack_wraps_within_page_one() {
+    // PHA with SP = $00, then PLA
+    let mut cpu = cpu_at(0x0400, &[0x48, 0x68]);
+    cpu.registers.sp = 0x00;
+    cpu.registers.a = 0xC3;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.bus[0x0100], 0xC3);
+    assert_eq!(cpu.registers.sp, 0xFF);
+
+    cpu.registers.a = 0x00;
+    cpu.bus[0x0100] = 0x3C;
+    cpu.registers.sp = 0xFF;
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.a, 0x3C);
+    assert_eq!(cpu.registers.sp, 0x00);
+}
+
+#[test]
+fn words_are_little_endian_and_wrap() {
+    let mut bus = FlatMemory::new();
+
+    bus.write_word(0x1000, 0x1234);
+    assert_eq!(bus[0x1000..0x1002], [0x34, 0x12]);
+    assert_eq!(bus.read_word(0x1000), 0x1234);
+
+    bus.write_word(0xFFFF, 0xABCD);
+    assert_eq!(bus[0xFFFF], 0xCD);
+    assert_eq!(bus[0x0000], 0xAB);
+    assert_eq!(bus.read_word(0xFFFF), 0xABCD);
+}
@@ -0,0 +1,276 @@
+use famines::{apu::APU, cartridge::Cartridge};
+
+fn cartridge() -> Cartridge {
+    let mut raw = vec![b'N', b'E', b'S', 0x1A, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    raw.resize(16 + 16384 + 8192, 0xFF);
+    Cartridge::new(&raw).unwrap()
+}
+
+#[test]
+fn length_counters_show_in_status() {
+    let mut apu = APU::new();
+    let mut cartridge = cartridge();
+
+    // Loading while disabled is ignored.
+    apu.write_register(0x4003, 0x08);
+    assert_eq!(apu.read_register(0x4015), 0x00);
+
+    apu.write_register(0x4015, 0x0F);
+    apu.write_register(0x4003, 0x08); // Length index 1: 254.
+    apu.write_register(0x400B, 0x18); // Length index 3: 2.
+    assert_eq!(
+        apu.read_register(0x4015),
+        APU::STATUS_PULSE1 | APU::STATUS_TRIANGLE
+    );
+
+    // Two half frames later the triangle has run out.
+    apu.write_register(0x4017, APU::FRAME_IRQ_INHIBIT);
+    apu.tick(30000, &mut cartridge);
+    assert_eq!(apu.read_register(0x4015), APU::STATUS_PULSE1);
+
+    apu.write_register(0x4015, 0x00);
+    assert_eq!(apu.read_register(0x4015), 0x00);
+}
+
+#[test]
+fn length_counter_halt() {
+    let mut apu = APU::new();
+    let mut cartridge = cartridge();
+    apu.write_register(0x4017, APU::FRAME_IRQ_INHIBIT);
+    apu.write_register(0x4015, 0x08);
+    apu.write_register(0x400C, 0x20);
+    apu.write_register(0x400F, 0x18);
+    apu.tick(100_000, &mut cartridge);
+    assert_eq!(apu.read_register(0x4015), APU::STATUS_NOISE);
+}
+
+#[test]
+fn four_step_mode_raises_frame_irq() {
+    let mut apu = APU::new();
+    let mut cartridge = cartridge();
+    apu.write_register(0x4017, 0x00);
+
+    apu.tick(29000, &mut cartridge);
+    assert!(!apu.irq());
+    apu.tick(1000, &mut cartridge);
+    assert!(apu.irq());
+
+    // Reading the status acknowledges it.
+    assert_ne!(apu.read_register(0x4015) & APU::STATUS_FRAME_IRQ, 0);
+    assert!(!apu.irq());
+}
+
+#[test]
+fn five_step_mode_and_inhibit_never_raise_frame_irq() {
+    let mut cartridge = cartridge();
+    for value in [APU::FRAME_FIVE_STEP, APU::FRAME_IRQ_INHIBIT] {
+        let mut apu = APU::new();
+        apu.write_register(0x4017, value);
+        apu.tick(100_000, &mut cartridge);
+        assert!(!apu.irq());
+    }
+}
+
+#[test]
+fn pulse_outputs_constant_volume() {
+    let mut apu = APU::new();
+    let mut cartridge = cartridge();
+    apu.write_register(0x4015, 0x01);
+    apu.write_register(0x4000, 0b1011_1010); // 50% duty, halt, constant volume 10.
+    apu.write_register(0x4002, 0xFF);
+    apu.write_register(0x4003, 0x00);
+
+    let mut levels = std::collections::BTreeSet::new();
+    for _ in 0..2000 {
+        apu.tick(1, &mut cartridge);
+        levels.insert(apu.pulse1.output());
+    }
+    assert_eq!(levels.into_iter().collect::<Vec<_>>(), vec![0, 10]);
+}
+
+#[test]
+fn sweep_mutes_low_periods() {
+    let mut apu = APU::new();
+    let mut cartridge = cartridge();
+    apu.write_register(0x4015, 0x01);
+    apu.write_register(0x4000, 0b1011_1111);
+    apu.write_register(0x4002, 0x07); // Period < 8.
+    apu.write_register(0x4003, 0x00);
+
+    for _ in 0..100 {
+        apu.tick(1, &mut cartridge);
+        assert_eq!(apu.pulse1.output(), 0);
+    }
+}
+
+#[test]
+fn noise_produces_both_levels() {
+    let mut apu = APU::new();
+    let mut cartridge = cartridge();
+    apu.write_register(0x4015, 0x08);
+    apu.write_register(0x400C, 0b0011_0101);
+    apu.write_register(0x400E, 0x00);
+    apu.write_register(0x400F, 0x00);
+
+    let mut levels = std::collections::BTreeSet::new();
+    for _ in 0..1000 {
+        apu.tick(1, &mut cartridge);
+        levels.insert(apu.noise.output());
+    }
+    assert_eq!(levels.into_iter().collect::<Vec<_>>(), vec![0, 5]);
+}
+
+#[test]
+fn dmc_plays_sample_and_raises_irq() {
+    let mut apu = APU::new();
+    let mut cartridge = cartridge();
+    apu.write_register(0x4017, APU::FRAME_IRQ_INHIBIT);
+    apu.write_register(0x4010, 0x8F); // IRQ enabled, fastest rate.
+    apu.write_register(0x4011, 0x40);
+    apu.write_register(0x4012, 0x00); // $C000, filled with $FF.
+    apu.write_register(0x4013, 0x01); // 17 bytes.
+    apu.write_register(0x4015, 0x10);
+    assert_ne!(apu.read_register(0x4015) & APU::STATUS_DMC, 0);
+
+    apu.tick(1, &mut cartridge);
+    assert_eq!(apu.take_stall(), 4);
+
+    apu.tick(17 * 8 * 54, &mut cartridge);
+    assert_eq!(apu.read_register(0x4015) & APU::STATUS_DMC, 0);
+    assert!(apu.irq());
+    assert!(apu.dmc.output() > 0x40);
+
+    // Writing $4015 acknowledges the DMC IRQ.
+    apu.write_register(0x4015, 0x00);
+    assert!(!apu.irq());
+}
+
+#[test]
+fn triangle_needs_linear_counter() {
+    let mut apu = APU::new();
+    let mut cartridge = cartridge();
+    apu.write_register(0x4017, APU::FRAME_IRQ_INHIBIT);
+    apu.write_register(0x4015, 0x04);
+    apu.write_register(0x4008, 0x00); // Linear counter reloads to 0.
+    apu.write_register(0x400A, 0x10);
+    apu.write_register(0x400B, 0x08);
+
+    let start = apu.triangle.output();
+    apu.tick(20000, &mut cartridge);
+    assert_eq!(apu.triangle.output(), start);
+
+    apu.write_register(0x4008, 0xFF);
+    apu.write_register(0x400B, 0x08);
+    apu.tick(8000, &mut cartridge);
+    let levels: std::collections::BTreeSet<_> = (0..2000)
+        .map(|_| {
+            apu.tick(1, &mut cartridge);
+            apu.triangle.output()
+        })
+        .collect();
+    assert_eq!(levels.len(), 16);
+}
+
+/// Plays a 50% duty square wave at full volume on pulse 1.
+fn square_wave(apu: &mut APU, period: u16) {
+    apu.write_register(0x4015, 0x01);
+    apu.write_register(0x4000, 0b1011_1111);
+    apu.write_register(0x4002, period as u8);
+    apu.write_register(0x4003, (period >> 8) as u8);
+}
+
+#[test]
+fn mixer_is_nonlinear() {
+    let mixer = famines::apu::mixer::Mixer::new();
+    assert_eq!(mixer.mix(0, 0, 0, 0, 0), 0.0);
+    assert!((mixer.mix(15, 15, 0, 0, 0) - 0.2575).abs() < 0.0001);
+    assert!((mixer.mix(0, 0, 15, 15, 127) - 0.7425).abs() < 0.0001);
+    // Two channels together are quieter than twice one of them.
+    assert!(mixer.mix(15, 15, 0, 0, 0) < 2.0 * mixer.mix(15, 0, 0, 0, 0));
+}
+
+#[test]
+fn resamples_to_the_host_rate() {
+    let mut cartridge = cartridge();
+    for rate in [44_100, 48_000] {
+        let mut apu = APU::new();
+        apu.set_sample_rate(rate);
+        apu.tick(famines::apu::CPU_CLOCK_RATE as usize / 10, &mut cartridge);
+
+        let expected = rate as usize / 10;
+        assert!(
+            apu.samples.len().abs_diff(expected) <= 1,
+            "{} at {}Hz",
+            apu.samples.len(),
+            rate
+        );
+    }
+}
+
+#[test]
+fn square_wave_keeps_its_pitch() {
+    let mut apu = APU::new();
+    let mut cartridge = cartridge();
+    apu.write_register(0x4017, APU::FRAME_IRQ_INHIBIT);
+    // 1789773 / (16 * (253 + 1)) = ~440Hz.
+    square_wave(&mut apu, 253);
+    apu.tick(famines::apu::CPU_CLOCK_RATE as usize / 2, &mut cartridge);
+
+    let mut samples = vec![0.0; apu.samples.len()];
+    let count = apu.samples.drain_f32(&mut samples);
+    assert_eq!(count, samples.len());
+    assert!(apu.samples.is_empty());
+
+    // Skip the filter settling, then count rising zero crossings.
+    let samples = &samples[2000..];
+    let crossings = samples
+        .windows(2)
+        .filter(|pair| pair[0] < 0.0 && pair[1] >= 0.0)
+        .count();
+    let seconds = samples.len() as f64 / 44_100.0;
+    let frequency = crossings as f64 / seconds;
+    assert!((frequency - 440.0).abs() < 5.0, "{}Hz", frequency);
+
+    // The band-limited steps ring a little, but stay close to the 0.15 swing.
+    let peak = samples
+        .iter()
+        .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+    assert!(peak < 0.2, "{}", peak);
+}
+
+#[test]
+fn drains_i16_samples() {
+    let mut apu = APU::new();
+    let mut cartridge = cartridge();
+    square_wave(&mut apu, 100);
+    apu.tick(20_000, &mut cartridge);
+
+    let mut samples = [0i16; 64];
+    assert_eq!(apu.samples.drain_i16(&mut samples), 64);
+    assert!(samples.iter().any(|&sample| sample != 0));
+}
+
+#[test]
+fn dumps_wav_files() {
+    let path = std::env::temp_dir().join(format!("famines-{}.wav", std::process::id()));
+    let mut apu = APU::new();
+    let mut cartridge = cartridge();
+    square_wave(&mut apu, 253);
+
+    apu.dump_wav(&path).unwrap();
+    apu.tick(famines::apu::CPU_CLOCK_RATE as usize / 100, &mut cartridge);
+    let samples = apu.samples.len() as u32;
+    apu.stop_wav().unwrap();
+
+    let wav = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let u32_at = |offset: usize| u32::from_le_bytes(wav[offset..offset + 4].try_into().unwrap());
+    assert_eq!(&wav[0..4], b"RIFF");
+    assert_eq!(&wav[8..16], b"WAVEfmt ");
+    assert_eq!(u32_at(24), 44_100);
+    assert_eq!(&wav[36..40], b"data");
+    assert_eq!(u32_at(40), samples * 2);
+    assert_eq!(u32_at(4), 36 + samples * 2);
+    assert_eq!(wav.len(), 44 + samples as usize * 2);
+}
@@ -0,0 +1,51 @@
+use famines::{bus::Bus, cartridge::Cartridge, controller::Controller, memory::Memory};
+
+fn bus() -> Bus {
+    let mut raw = vec![b'N', b'E', b'S', 0x1A, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    raw.resize(16 + 16384 + 8192, 0xEA);
+    Bus::new(Cartridge::new(&raw).unwrap())
+}
+
+#[test]
+fn write_only_registers_read_open_bus() {
+    let mut bus = bus();
+    for address in (0x4000..=0x4014).chain(0x4018..=0x401F) {
+        assert_eq!(bus.read_byte(address), 0x40, "${:04X}", address);
+    }
+}
+
+#[test]
+fn writes_to_unused_registers_are_ignored() {
+    let mut bus = bus();
+    for address in 0x4018..=0x401F {
+        bus.write_byte(address, 0xFF);
+        assert_eq!(bus.read_byte(address), 0x40, "${:04X}", address);
+    }
+}
+
+#[test]
+fn controller_ports_keep_the_open_bus_bits() {
+    let mut bus = bus();
+    bus.controllers[0].set_buttons(Controller::A);
+    bus.controllers[1].set_buttons(Controller::A);
+    bus.write_byte(0x4016, 1);
+    bus.write_byte(0x4016, 0);
+
+    // Only bit 0 comes from the controller, bits 5-7 are open bus.
+    assert_eq!(bus.read_byte(0x4016), 0x41);
+    assert_eq!(bus.read_byte(0x4017), 0x41);
+    for _ in 0..7 {
+        assert_eq!(bus.read_byte(0x4016) & 0xE0, 0x40);
+        assert_eq!(bus.read_byte(0x4017) & 0xE0, 0x40);
+    }
+}
+
+#[test]
+fn decodes_ram_and_cartridge() {
+    let mut bus = bus();
+    bus.write_byte(0x0123, 0x5A);
+    assert_eq!(bus.read_byte(0x0923), 0x5A);
+    assert_eq!(bus.read_byte(0x1923), 0x5A);
+    assert_eq!(bus.read_byte(0x8000), 0xEA);
+    assert_eq!(bus.read_byte(0xC000), 0xEA);
+}
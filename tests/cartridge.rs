@@ -0,0 +1,377 @@
+use famines::{
+    cartridge::{
+        header::{CartridgeHeader, ConsoleType, Format, Timing},
+        Cartridge, CartridgeError,
+    },
+    memory::Memory,
+};
+
+/// A tiny linear congruential generator, good enough to produce junk ROMs.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.0 >> 33
+    }
+
+    fn byte(&mut self) -> u8 {
+        self.next() as u8
+    }
+
+    fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.byte()).collect()
+    }
+}
+
+fn rom(header: [u8; 16], prg_banks: usize, chr_banks: usize) -> Vec<u8> {
+    let mut raw = header.to_vec();
+    raw.resize(16 + prg_banks * 16384 + chr_banks * 8192, 0xEA);
+    raw
+}
+
+fn nrom() -> Vec<u8> {
+    rom(
+        [b'N', b'E', b'S', 0x1A, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        1,
+        1,
+    )
+}
+
+/// Loading must never panic, and a loaded cartridge must survive every access.
+fn load(raw: &[u8]) -> Result<Cartridge, CartridgeError> {
+    let mut cartridge = Cartridge::new(raw)?;
+    for address in 0x4020..=0xFFFF {
+        cartridge.read_byte(address);
+    }
+    for address in (0x4020..=0xFFFF).step_by(0x3F) {
+        cartridge.write_byte(address, address as u8);
+    }
+    for address in 0x0000..0x2000 {
+        cartridge.read_chr(address);
+        cartridge.write_chr(address, 0);
+    }
+    Ok(cartridge)
+}
+
+#[test]
+fn loads_nrom() {
+    let cartridge = load(&nrom()).unwrap();
+    assert_eq!(cartridge.header.format, Format::INes);
+    assert_eq!(cartridge.prg.len(), 16384);
+    assert_eq!(cartridge.chr.len(), 8192);
+}
+
+#[test]
+fn rejects_bad_magic() {
+    let mut raw = nrom();
+    raw[3] = 0x1B;
+    assert_eq!(Cartridge::new(&raw).err(), Some(CartridgeError::BadMagic));
+    assert_eq!(
+        Cartridge::new(b"PK\x03\x04").err(),
+        Some(CartridgeError::BadMagic)
+    );
+}
+
+#[test]
+fn rejects_every_truncation() {
+    let raw = nrom();
+    for len in 0..raw.len() {
+        let error = Cartridge::new(&raw[..len]).err().unwrap();
+        let expected = match len {
+            0..=15 => CartridgeError::TruncatedHeader { size: len },
+            16..=16399 => CartridgeError::TruncatedPrg {
+                expected: 16384,
+                actual: len - 16,
+            },
+            _ => CartridgeError::TruncatedChr {
+                expected: 8192,
+                actual: len - 16400,
+            },
+        };
+        assert_eq!(error, expected);
+    }
+}
+
+#[test]
+fn rejects_truncated_trainer() {
+    let mut raw = nrom();
+    raw[6] |= 0b100;
+    assert_eq!(
+        Cartridge::new(&raw[..100]).err(),
+        Some(CartridgeError::TruncatedTrainer {
+            expected: 512,
+            actual: 84
+        })
+    );
+}
+
+#[test]
+fn rejects_unsupported_mapper() {
+    let mut raw = nrom();
+    raw[6] = 0xF0;
+    raw[7] = 0xF0;
+    let error = Cartridge::new(&raw).err().unwrap();
+    assert_eq!(
+        error,
+        CartridgeError::UnsupportedMapper {
+            mapper: 255,
+            submapper: 0
+        }
+    );
+    assert_eq!(error.to_string(), "Mapper 255.0 is not supported");
+
+    let error: Box<dyn std::error::Error> = Box::new(error);
+    assert!(error.source().is_none());
+}
+
+#[test]
+fn rejects_oversized_nes2_roms() {
+    // Exponent 63 with multiplier 7 overflows any address space.
+    let header = [
+        b'N', b'E', b'S', 0x1A, 0xFF, 0, 0, 0x08, 0, 0x0F, 0, 0, 0, 0, 0, 0,
+    ];
+    assert!(matches!(
+        Cartridge::new(&header).err(),
+        Some(CartridgeError::UnsupportedFormat(_) | CartridgeError::TruncatedPrg { .. })
+    ));
+}
+
+#[test]
+fn parses_nes2_header() {
+    let header = [
+        b'N', b'E', b'S', 0x1A, 0x02, 0x01, 0x43, 0x09, 0x21, 0x10, 0x70, 0x07, 0x03, 0x00, 0x01,
+        0x05,
+    ];
+    let header = CartridgeHeader::parse(&header).unwrap();
+    assert_eq!(header.format, Format::Nes2);
+    assert_eq!(header.mapper, 0x104);
+    assert_eq!(header.submapper, 2);
+    assert_eq!(header.prg_rom_size, 2 * 16384);
+    assert_eq!(header.chr_rom_size, 0x101 * 8192);
+    assert_eq!(header.prg_nvram_size, 8192);
+    assert_eq!(header.prg_ram_size, 0);
+    assert_eq!(header.chr_ram_size, 8192);
+    assert_eq!(header.timing, Timing::Dendy);
+    assert_eq!(
+        header.console,
+        ConsoleType::VsSystem {
+            ppu: 0,
+            hardware: 0
+        }
+    );
+    assert_eq!(header.misc_roms, 1);
+    assert_eq!(header.expansion_device, 5);
+    assert!(header.battery);
+}
+
+#[test]
+fn parses_nes2_exponent_sizes() {
+    // 2^10 * (1 * 2 + 1) bytes of PRG-ROM.
+    let header = [
+        b'N', b'E', b'S', 0x1A, 0x29, 0, 0, 0x08, 0, 0x0F, 0, 0, 0, 0, 0, 0,
+    ];
+    assert_eq!(CartridgeHeader::parse(&header).unwrap().prg_rom_size, 3072);
+}
+
+#[test]
+fn survives_random_bytes() {
+    let mut lcg = Lcg(0x5EED);
+    for _ in 0..2000 {
+        let len = lcg.next() as usize % 64;
+        let raw = lcg.bytes(len);
+        let _ = load(&raw);
+    }
+}
+
+#[test]
+fn survives_random_headers() {
+    let mut lcg = Lcg(0xFA417E5);
+    let mut loaded = 0;
+    for _ in 0..500 {
+        let mut header = [0; 16];
+        header.copy_from_slice(&lcg.bytes(16));
+        header[..4].copy_from_slice(b"NES\x1A");
+        // Keep the sizes small so a fair share of the images are complete.
+        header[4] = lcg.byte() % 4;
+        header[5] = lcg.byte() % 3;
+        let mapper = [0, 1, 2, 3, 4, 7, 66][lcg.next() as usize % 7];
+        header[6] = (header[6] & 0x0F) | (mapper << 4);
+        header[7] = (header[7] & 0x0F) | (mapper & 0xF0);
+        if lcg.byte() & 0x07 != 0 {
+            header[9] = 0;
+        }
+
+        let body = lcg.next() as usize % 0x12000;
+        let mut raw = header.to_vec();
+        raw.extend(lcg.bytes(body));
+        if load(&raw).is_ok() {
+            loaded += 1;
+        }
+    }
+    assert!(loaded > 0);
+}
+
+#[test]
+fn allocates_chr_ram_without_chr_rom() {
+    let raw = rom(
+        [
+            b'N', b'E', b'S', 0x1A, 2, 0, 0x20, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ],
+        2,
+        0,
+    );
+    let mut cartridge = Cartridge::new(&raw).unwrap();
+    assert_eq!(cartridge.chr.len(), 8192);
+
+    cartridge.write_chr(0x1234, 0x5A);
+    assert_eq!(cartridge.read_chr(0x1234), 0x5A);
+}
+
+#[test]
+fn sizes_chr_ram_from_nes2_header() {
+    // 32KB of CHR-RAM (64 << 9) on an AxROM board.
+    let raw = rom(
+        [
+            b'N', b'E', b'S', 0x1A, 2, 0, 0x70, 0x08, 0, 0, 0, 0x09, 0, 0, 0, 0,
+        ],
+        2,
+        0,
+    );
+    let mut cartridge = Cartridge::new(&raw).unwrap();
+    assert_eq!(cartridge.chr.len(), 32768);
+
+    cartridge.write_chr(0x0000, 0x11);
+    cartridge.write_chr(0x1FFF, 0x22);
+    assert_eq!(cartridge.read_chr(0x0000), 0x11);
+    assert_eq!(cartridge.read_chr(0x1FFF), 0x22);
+}
+
+#[test]
+fn chr_rom_is_read_only() {
+    let mut cartridge = Cartridge::new(&nrom()).unwrap();
+    cartridge.write_chr(0x0000, 0x00);
+    assert_eq!(cartridge.read_chr(0x0000), 0xEA);
+}
+
+fn battery_nrom() -> Vec<u8> {
+    let mut raw = nrom();
+    raw[6] |= 0b10;
+    raw
+}
+
+#[test]
+fn maps_prg_ram_at_6000() {
+    let mut cartridge = Cartridge::new(&nrom()).unwrap();
+    assert_eq!(cartridge.prg_ram.len(), 8192);
+    assert!(!cartridge.has_battery());
+
+    cartridge.write_byte(0x6000, 0x12);
+    cartridge.write_byte(0x7FFF, 0x34);
+    assert_eq!(cartridge.read_byte(0x6000), 0x12);
+    assert_eq!(cartridge.read_byte(0x7FFF), 0x34);
+}
+
+#[test]
+fn sizes_prg_ram_from_nes2_header() {
+    // 2KB of PRG-RAM (64 << 5) and 32KB of PRG-NVRAM (64 << 9).
+    let raw = rom(
+        [
+            b'N', b'E', b'S', 0x1A, 1, 1, 0x12, 0x08, 0, 0, 0x95, 0, 0, 0, 0, 0,
+        ],
+        1,
+        1,
+    );
+    let cartridge = Cartridge::new(&raw).unwrap();
+    assert_eq!(cartridge.prg_ram.len(), 2048 + 32768);
+    assert!(cartridge.has_battery());
+}
+
+#[test]
+fn exports_and_imports_saves() {
+    let mut cartridge = Cartridge::new(&battery_nrom()).unwrap();
+    cartridge.write_byte(0x6123, 0xAB);
+    let save = cartridge.export_save();
+    assert_eq!(save.len(), 8192);
+
+    let mut cartridge = Cartridge::new(&battery_nrom()).unwrap();
+    cartridge.import_save(&save).unwrap();
+    assert_eq!(cartridge.read_byte(0x6123), 0xAB);
+
+    assert_eq!(
+        cartridge.import_save(&save[..100]),
+        Err(CartridgeError::SaveSizeMismatch {
+            expected: 8192,
+            actual: 100
+        })
+    );
+}
+
+#[test]
+fn autosaves_next_to_the_rom() {
+    let dir = std::env::temp_dir().join(format!("famines-autosave-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let rom_path = dir.join("game.nes");
+    let save_path = dir.join("game.sav");
+
+    {
+        let mut cartridge = Cartridge::new(&battery_nrom()).unwrap();
+        cartridge.enable_autosave(&rom_path).unwrap();
+        cartridge.write_byte(0x6000, 0x42);
+    }
+    assert_eq!(std::fs::read(&save_path).unwrap()[0], 0x42);
+
+    let mut cartridge = Cartridge::new(&battery_nrom()).unwrap();
+    cartridge.enable_autosave(&rom_path).unwrap();
+    assert_eq!(cartridge.read_byte(0x6000), 0x42);
+
+    // Without a battery nothing is loaded or written.
+    let mut cartridge = Cartridge::new(&nrom()).unwrap();
+    std::fs::remove_file(&save_path).unwrap();
+    cartridge.enable_autosave(&rom_path).unwrap();
+    cartridge.write_byte(0x6000, 0x42);
+    drop(cartridge);
+    assert!(!save_path.exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn maps_trainer_at_7000() {
+    let mut raw = nrom();
+    raw[6] |= 0b100;
+    let trainer: Vec<u8> = (0..512).map(|i| i as u8).collect();
+    raw.splice(16..16, trainer.iter().copied());
+
+    let mut cartridge = Cartridge::new(&raw).unwrap();
+    assert_eq!(cartridge.trainer.as_deref(), Some(&trainer[..]));
+    assert_eq!(cartridge.prg.len(), 16384);
+    assert_eq!(cartridge.read_byte(0x8000), 0xEA);
+    for (offset, &byte) in trainer.iter().enumerate() {
+        assert_eq!(cartridge.read_byte(0x7000 + offset as u16), byte);
+    }
+    assert_eq!(cartridge.read_byte(0x7200), 0x00);
+}
+
+#[test]
+fn maps_trainer_on_boards_without_prg_ram() {
+    // UxROM maps nothing at $6000-$7FFF.
+    let mut raw = rom(
+        [b'N', b'E', b'S', 0x1A, 2, 0, 0x24, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        2,
+        0,
+    );
+    let trainer: Vec<u8> = (0..512).map(|i| (i as u8) ^ 0x5A).collect();
+    raw.splice(16..16, trainer.iter().copied());
+
+    let mut cartridge = Cartridge::new(&raw).unwrap();
+    for (offset, &byte) in trainer.iter().enumerate() {
+        assert_eq!(cartridge.read_byte(0x7000 + offset as u16), byte);
+    }
+    assert_eq!(cartridge.read_byte(0x6FFF), 0x00);
+    assert_eq!(cartridge.read_byte(0x7200), 0x00);
+    assert_eq!(cartridge.read_byte(0x8000), 0xEA);
+}
@@ -0,0 +1,305 @@
+use famines::{
+    cpu::{registers::Registers, CpuVariant, CPU},
+    memory::{flat::FlatMemory, Address, Byte},
+};
+
+fn cpu(program: &[Byte]) -> CPU<FlatMemory> {
+    let mut memory = FlatMemory::new();
+    memory.load(0x0400, program);
+
+    let mut cpu = CPU::new(memory);
+    cpu.variant = CpuVariant::Cmos65C02;
+    cpu.registers.pc = 0x0400;
+    cpu
+}
+
+#[test]
+fn stz_stores_zero() {
+    // STZ $10; STZ $1234,X
+    let mut cpu = cpu(&[0x64, 0x10, 0x9E, 0x34, 0x12]);
+    cpu.registers.x = 0x01;
+    cpu.bus[0x0010] = 0xFF;
+    cpu.bus[0x1235] = 0xFF;
+
+    assert!(cpu.step());
+    assert!(cpu.step());
+    assert_eq!(cpu.bus[0x0010], 0x00);
+    assert_eq!(cpu.bus[0x1235], 0x00);
+    assert_eq!(cpu.cycles, 3 + 5);
+}
+
+#[test]
+fn bra_always_branches() {
+    // BRA +$10
+    let mut cpu = cpu(&[0x80, 0x10]);
+    cpu.registers.flags = 0xFF;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.pc, 0x0412);
+    assert_eq!(cpu.cycles, 3);
+}
+
+#[test]
+fn pushes_and_pulls_index_registers() {
+    // PHX; PHY; PLX; PLY
+    let mut cpu = cpu(&[0xDA, 0x5A, 0xFA, 0x7A]);
+    cpu.registers.x = 0x12;
+    cpu.registers.y = 0x80;
+
+    for _ in 0..4 {
+        assert!(cpu.step());
+    }
+    assert_eq!((cpu.registers.x, cpu.registers.y), (0x80, 0x12));
+    assert!(!cpu.registers.get_flag(Registers::NEGATIVE_FLAG));
+    assert_eq!(cpu.cycles, 3 + 3 + 4 + 4);
+}
+
+#[test]
+fn test_and_set_or_reset_bits() {
+    // TSB $10; TRB $1234
+    let mut cpu = cpu(&[0x04, 0x10, 0x1C, 0x34, 0x12]);
+    cpu.registers.a = 0x0F;
+    cpu.bus[0x0010] = 0xF0;
+    cpu.bus[0x1234] = 0xFF;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.bus[0x0010], 0xFF);
+    assert!(cpu.registers.get_flag(Registers::ZERO_FLAG));
+
+    assert!(cpu.step());
+    assert_eq!(cpu.bus[0x1234], 0xF0);
+    assert!(!cpu.registers.get_flag(Registers::ZERO_FLAG));
+    assert_eq!(cpu.cycles, 5 + 6);
+}
+
+#[test]
+fn increments_and_decrements_accumulator() {
+    // INC A; DEC A; DEC A
+    let mut cpu = cpu(&[0x1A, 0x3A, 0x3A]);
+    cpu.registers.a = 0xFF;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.a, 0x00);
+    assert!(cpu.registers.get_flag(Registers::ZERO_FLAG));
+
+    assert!(cpu.step());
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.a, 0xFE);
+    assert!(cpu.registers.get_flag(Registers::NEGATIVE_FLAG));
+    assert_eq!(cpu.cycles, 6);
+}
+
+#[test]
+fn zero_page_indirect_addressing() {
+    // LDA ($FF); STA ($10)
+    let mut cpu = cpu(&[0xB2, 0xFF, 0x92, 0x10]);
+    cpu.bus[0x00FF] = 0x34;
+    cpu.bus[0x0000] = 0x12;
+    cpu.bus[0x1234] = 0x5A;
+    cpu.bus[0x0010] = 0x00;
+    cpu.bus[0x0011] = 0x20;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.a, 0x5A);
+    assert!(cpu.step());
+    assert_eq!(cpu.bus[0x2000], 0x5A);
+    assert_eq!(cpu.cycles, 10);
+}
+
+#[test]
+fn bit_immediate_only_affects_zero() {
+    // BIT #$C0
+    let mut cpu = cpu(&[0x89, 0xC0]);
+    cpu.registers.a = 0x01;
+
+    assert!(cpu.step());
+    assert!(cpu.registers.get_flag(Registers::ZERO_FLAG));
+    assert!(!cpu.registers.get_flag(Registers::NEGATIVE_FLAG));
+    assert!(!cpu.registers.get_flag(Registers::OVERFLOW_FLAG));
+}
+
+#[test]
+fn resets_and_sets_memory_bits() {
+    // RMB0 $10; SMB7 $10
+    let mut cpu = cpu(&[0x07, 0x10, 0xF7, 0x10]);
+    cpu.bus[0x0010] = 0x01;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.bus[0x0010], 0x00);
+    assert!(cpu.step());
+    assert_eq!(cpu.bus[0x0010], 0x80);
+    assert_eq!(cpu.cycles, 10);
+}
+
+#[test]
+fn branches_on_memory_bits() {
+    // BBR3 $10,+$10 not taken, then BBS3 $10,+$10 taken
+    let mut cpu = cpu(&[0x3F, 0x10, 0x10, 0xBF, 0x10, 0x10]);
+    cpu.bus[0x0010] = 0x08;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.pc, 0x0403);
+    assert_eq!(cpu.cycles, 5);
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.pc, 0x0416);
+    assert_eq!(cpu.cycles, 5 + 6);
+}
+
+#[test]
+fn jumps_through_indexed_pointer() {
+    // JMP ($1200,X)
+    let mut cpu = cpu(&[0x7C, 0x00, 0x12]);
+    cpu.registers.x = 0x04;
+    cpu.bus[0x1204] = 0x78;
+    cpu.bus[0x1205] = 0x56;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.pc, 0x5678);
+    assert_eq!(cpu.cycles, 6);
+}
+
+#[test]
+fn indirect_jump_crosses_pages() {
+    // JMP ($10FF)
+    let mut cpu = cpu(&[0x6C, 0xFF, 0x10]);
+    cpu.bus[0x10FF] = 0x34;
+    cpu.bus[0x1000] = 0x56;
+    cpu.bus[0x1100] = 0x12;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.pc, 0x1234);
+    assert_eq!(cpu.cycles, 6);
+}
+
+#[test]
+fn shifts_only_pay_for_crossing_a_page() {
+    // (X, target, cycles) for ASL $12F0,X
+    let cases: [(Byte, Address, usize); 2] = [(0x0F, 0x12FF, 6), (0x10, 0x1300, 7)];
+
+    for (x, target, cycles) in cases {
+        let mut cpu = cpu(&[0x1E, 0xF0, 0x12]);
+        cpu.registers.x = x;
+        cpu.bus[target as usize] = 0x81;
+
+        assert!(cpu.step());
+        assert_eq!(cpu.bus[target as usize], 0x02);
+        assert!(cpu.registers.get_flag(Registers::CARRY_FLAG));
+        assert_eq!(cpu.cycles, cycles, "X = {:02X}", x);
+    }
+
+    // INC and DEC always take 7.
+    let mut cpu = cpu(&[0xFE, 0xF0, 0x12]);
+    cpu.registers.x = 0x0F;
+    assert!(cpu.step());
+    assert_eq!(cpu.bus[0x12FF], 0x01);
+    assert_eq!(cpu.cycles, 7);
+}
+
+#[test]
+fn decimal_mode_takes_an_extra_cycle() {
+    // ADC #$01 with D set, then SEC; SBC #$01
+    let mut cpu = cpu(&[0x69, 0x01, 0x38, 0xE9, 0x01]);
+    cpu.registers.flags |= Registers::DECIMAL_FLAG;
+    cpu.registers.a = 0x09;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.a, 0x10);
+    assert_eq!(cpu.cycles, 3);
+
+    assert!(cpu.step());
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.a, 0x09);
+    assert_eq!(cpu.cycles, 3 + 2 + 3);
+}
+
+#[test]
+fn interrupts_clear_decimal_flag() {
+    // BRK
+    let mut cpu = cpu(&[0x00]);
+    cpu.registers.flags |= Registers::DECIMAL_FLAG;
+    cpu.bus[0xFFFE] = 0x00;
+    cpu.bus[0xFFFF] = 0x80;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.pc, 0x8000);
+    assert!(!cpu.registers.get_flag(Registers::DECIMAL_FLAG));
+}
+
+#[test]
+fn undefined_opcodes_are_nops() {
+    // (opcode, length, cycles)
+    let nops: [(Byte, u16, usize); 7] = [
+        (0x02, 2, 2),
+        (0x44, 2, 3),
+        (0xF4, 2, 4),
+        (0x03, 1, 1),
+        (0xFB, 1, 1),
+        (0xDC, 3, 4),
+        (0x5C, 3, 8),
+    ];
+
+    for (opcode, length, cycles) in nops {
+        let mut cpu = cpu(&[opcode, 0x10, 0x12]);
+        let registers = (cpu.registers.a, cpu.registers.x, cpu.registers.flags);
+
+        assert!(cpu.step());
+        assert_eq!(cpu.registers.pc, 0x0400 + length, "opcode {:02X}", opcode);
+        assert_eq!(cpu.cycles, cycles, "opcode {:02X}", opcode);
+        assert_eq!(
+            (cpu.registers.a, cpu.registers.x, cpu.registers.flags),
+            registers
+        );
+    }
+}
+
+#[test]
+fn wai_waits_for_interrupt_and_stp_stops() {
+    // WAI; STP
+    let mut cpu = cpu(&[0xCB, 0xDB]);
+    cpu.registers.set_flag(Registers::IRQ_FLAG, true);
+
+    assert!(cpu.step());
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.pc, 0x0400);
+
+    // A masked IRQ ends the wait without being serviced.
+    cpu.bus.irq = true;
+    cpu.tick(0);
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.pc, 0x0401);
+
+    assert!(!cpu.step());
+    assert_eq!(cpu.registers.pc, 0x0401);
+}
+
+#[test]
+fn cycle_accurate_mode_matches_cycle_counts() {
+    let programs: [&[Byte]; 11] = [
+        &[0x80, 0x10],       // BRA
+        &[0x92, 0x10],       // STA (zp)
+        &[0x04, 0x10],       // TSB zp
+        &[0x9E, 0xF0, 0x12], // STZ abs,X
+        &[0x7C, 0x00, 0x12], // JMP (abs,X)
+        &[0x6C, 0xFF, 0x10], // JMP (abs)
+        &[0x8F, 0x10, 0x70], // BBS0 zp,rel
+        &[0x5C, 0x00, 0x12], // NOP
+        &[0x1E, 0xD0, 0x12], // ASL abs,X
+        &[0x7E, 0xF0, 0x12], // ROR abs,X, crossing a page
+        &[0xFE, 0xD0, 0x12], // INC abs,X
+    ];
+
+    for program in programs {
+        let mut fast = cpu(program);
+        let mut accurate = cpu(program);
+        accurate.cycle_accurate = true;
+        for cpu in [&mut fast, &mut accurate] {
+            cpu.registers.x = 0x20;
+            cpu.bus[0x0010] = 0xFF;
+            assert!(cpu.step());
+        }
+
+        assert_eq!(accurate.cycles, fast.cycles, "program {:02X?}", program);
+        assert_eq!(accurate.registers.pc, fast.registers.pc);
+    }
+}
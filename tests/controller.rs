@@ -0,0 +1,69 @@
+use famines::{
+    bus::Bus, cartridge::Cartridge, controller::Controller, cpu::CPU, memory::Memory,
+};
+
+fn cpu() -> CPU<Bus> {
+    let mut raw = vec![b'N', b'E', b'S', 0x1A, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    raw.resize(16 + 16384 + 8192, 0);
+    CPU::new(Bus::new(Cartridge::new(&raw).unwrap()))
+}
+
+fn read_buttons(cpu: &mut CPU<Bus>, address: u16) -> Vec<u8> {
+    cpu.write_byte(0x4016, 1);
+    cpu.write_byte(0x4016, 0);
+    (0..10).map(|_| cpu.read_byte(address)).collect()
+}
+
+#[test]
+fn shifts_out_buttons_in_order() {
+    let mut cpu = cpu();
+    cpu.bus.controllers[0].set_buttons(Controller::A | Controller::START | Controller::RIGHT);
+
+    assert_eq!(
+        read_buttons(&mut cpu, 0x4016),
+        vec![0x41, 0x40, 0x40, 0x41, 0x40, 0x40, 0x40, 0x41, 0x41, 0x41]
+    );
+}
+
+#[test]
+fn second_port() {
+    let mut cpu = cpu();
+    cpu.bus.controllers[1].set_button(Controller::B, true);
+    cpu.bus.controllers[1].set_button(Controller::UP, true);
+    cpu.bus.controllers[1].set_button(Controller::UP, false);
+
+    assert_eq!(
+        read_buttons(&mut cpu, 0x4017)[..8],
+        [0x40, 0x41, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40]
+    );
+    assert!(read_buttons(&mut cpu, 0x4016)[..8]
+        .iter()
+        .all(|&bit| bit == 0x40));
+}
+
+#[test]
+fn strobe_keeps_returning_a() {
+    let mut cpu = cpu();
+    cpu.bus.controllers[0].set_buttons(Controller::A | Controller::B);
+    cpu.write_byte(0x4016, 1);
+    for _ in 0..4 {
+        assert_eq!(cpu.read_byte(0x4016), 0x41);
+    }
+
+    cpu.bus.controllers[0].set_buttons(Controller::B);
+    assert_eq!(cpu.read_byte(0x4016), 0x40);
+}
+
+#[test]
+fn latches_buttons_when_strobe_falls() {
+    let mut cpu = cpu();
+    cpu.bus.controllers[0].set_buttons(Controller::SELECT);
+    cpu.write_byte(0x4016, 1);
+    cpu.write_byte(0x4016, 0);
+
+    // Changes after the latch only show up on the next strobe.
+    cpu.bus.controllers[0].set_buttons(Controller::A);
+    assert_eq!(cpu.read_byte(0x4016), 0x40);
+    assert_eq!(cpu.read_byte(0x4016), 0x40);
+    assert_eq!(cpu.read_byte(0x4016), 0x41);
+}
@@ -0,0 +1,259 @@
+use famines::{
+    bus::CpuBus,
+    cpu::CPU,
+    memory::{Address, Byte, Memory},
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Access {
+    Read(Address),
+    Write(Address, Byte),
+}
+
+/// A flat 64KB bus that records every access and every tick.
+struct RecordingBus {
+    memory: Vec<Byte>,
+    accesses: Vec<Access>,
+    ticks: usize,
+}
+
+impl Memory for RecordingBus {
+    fn read_byte(&mut self, address: Address) -> Byte {
+        self.accesses.push(Access::Read(address));
+        self.memory[address as usize]
+    }
+
+    fn write_byte(&mut self, address: Address, value: Byte) {
+        self.accesses.push(Access::Write(address, value));
+        self.memory[address as usize] = value;
+    }
+}
+
+impl CpuBus for RecordingBus {
+    fn tick(&mut self, cycles: usize) -> usize {
+        // Every tick in cycle accurate mode must come with exactly one access.
+        self.ticks += cycles;
+        0
+    }
+}
+
+fn cpu(program: &[Byte], cycle_accurate: bool) -> CPU<RecordingBus> {
+    let mut cpu = CPU::new(RecordingBus {
+        memory: vec![0; 0x10000],
+        accesses: Vec::new(),
+        ticks: 0,
+    });
+    cpu.cycle_accurate = cycle_accurate;
+    cpu.bus.memory[0x0400..0x0400 + program.len()].copy_from_slice(program);
+    cpu.registers.pc = 0x0400;
+    cpu
+}
+
+/// Runs a single instruction placed at $0400 and returns the bus accesses it made.
+fn run(
+    program: &[Byte],
+    setup: impl FnOnce(&mut CPU<RecordingBus>),
+) -> (CPU<RecordingBus>, Vec<Access>) {
+    let mut cpu = cpu(program, true);
+    setup(&mut cpu);
+
+    assert!(cpu.step());
+    let accesses = std::mem::take(&mut cpu.bus.accesses);
+    assert_eq!(cpu.cycles, accesses.len());
+    assert_eq!(cpu.bus.ticks, accesses.len());
+    (cpu, accesses)
+}
+
+#[test]
+fn indexed_read_on_page_crossing_reads_unfixed_address() {
+    // LDA $12F0,X with X = $20
+    let (cpu, accesses) = run(&[0xBD, 0xF0, 0x12], |cpu| {
+        cpu.registers.x = 0x20;
+        cpu.bus.memory[0x1310] = 0x42;
+    });
+
+    assert_eq!(
+        accesses,
+        [
+            Access::Read(0x0400),
+            Access::Read(0x0401),
+            Access::Read(0x0402),
+            Access::Read(0x1210),
+            Access::Read(0x1310),
+        ]
+    );
+    assert_eq!(cpu.registers.a, 0x42);
+}
+
+#[test]
+fn indexed_read_within_page_skips_dummy_read() {
+    // LDA $1200,Y with Y = $10
+    let (_, accesses) = run(&[0xB9, 0x00, 0x12], |cpu| cpu.registers.y = 0x10);
+
+    assert_eq!(accesses.len(), 4);
+    assert_eq!(accesses[3], Access::Read(0x1210));
+}
+
+#[test]
+fn indexed_store_always_reads_first() {
+    // STA $1200,X with X = $10
+    let (_, accesses) = run(&[0x9D, 0x00, 0x12], |cpu| {
+        cpu.registers.a = 0x99;
+        cpu.registers.x = 0x10;
+    });
+
+    assert_eq!(
+        accesses[3..],
+        [Access::Read(0x1210), Access::Write(0x1210, 0x99)]
+    );
+}
+
+#[test]
+fn indirect_indexed_store_reads_unfixed_address() {
+    // STA ($10),Y with ($10) = $12F0 and Y = $20
+    let (_, accesses) = run(&[0x91, 0x10], |cpu| {
+        cpu.registers.a = 0x55;
+        cpu.registers.y = 0x20;
+        cpu.bus.memory[0x10] = 0xF0;
+        cpu.bus.memory[0x11] = 0x12;
+    });
+
+    assert_eq!(
+        accesses[2..],
+        [
+            Access::Read(0x0010),
+            Access::Read(0x0011),
+            Access::Read(0x1210),
+            Access::Write(0x1310, 0x55),
+        ]
+    );
+}
+
+#[test]
+fn read_modify_write_writes_twice() {
+    // INC $1234,X with X = $01
+    let (_, accesses) = run(&[0xFE, 0x33, 0x12], |cpu| {
+        cpu.registers.x = 0x01;
+        cpu.bus.memory[0x1234] = 0x7F;
+    });
+
+    assert_eq!(
+        accesses[3..],
+        [
+            Access::Read(0x1234),
+            Access::Read(0x1234),
+            Access::Write(0x1234, 0x7F),
+            Access::Write(0x1234, 0x80),
+        ]
+    );
+}
+
+#[test]
+fn zero_page_indexed_reads_base_address() {
+    // LDA $80,X with X = $05
+    let (_, accesses) = run(&[0xB5, 0x80], |cpu| cpu.registers.x = 0x05);
+
+    assert_eq!(accesses[2..], [Access::Read(0x0080), Access::Read(0x0085)]);
+}
+
+#[test]
+fn implied_instructions_read_next_byte() {
+    // INX
+    let (_, accesses) = run(&[0xE8], |_| {});
+
+    assert_eq!(accesses, [Access::Read(0x0400), Access::Read(0x0401)]);
+}
+
+#[test]
+fn jsr_and_rts_accesses() {
+    // JSR $1234
+    let (_, accesses) = run(&[0x20, 0x34, 0x12], |_| {});
+    assert_eq!(
+        accesses,
+        [
+            Access::Read(0x0400),
+            Access::Read(0x0401),
+            Access::Read(0x01FD),
+            Access::Write(0x01FD, 0x04),
+            Access::Write(0x01FC, 0x02),
+            Access::Read(0x0402),
+        ]
+    );
+
+    // RTS, returning to $0402 + 1
+    let (cpu, accesses) = run(&[0x60], |cpu| {
+        cpu.registers.sp = 0xFB;
+        cpu.bus.memory[0x01FC] = 0x02;
+        cpu.bus.memory[0x01FD] = 0x04;
+    });
+    assert_eq!(
+        accesses,
+        [
+            Access::Read(0x0400),
+            Access::Read(0x0401),
+            Access::Read(0x01FB),
+            Access::Read(0x01FC),
+            Access::Read(0x01FD),
+            Access::Read(0x0402),
+        ]
+    );
+    assert_eq!(cpu.registers.pc, 0x0403);
+}
+
+#[test]
+fn taken_branch_across_page_reads_twice() {
+    // BNE +$10 from $04F0
+    let mut cpu = cpu(&[], true);
+    cpu.bus.memory[0x04F0] = 0xD0;
+    cpu.bus.memory[0x04F1] = 0x10;
+    cpu.registers.pc = 0x04F0;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.pc, 0x0502);
+    assert_eq!(
+        cpu.bus.accesses,
+        [
+            Access::Read(0x04F0),
+            Access::Read(0x04F1),
+            Access::Read(0x04F2),
+            Access::Read(0x0402),
+        ]
+    );
+}
+
+#[test]
+fn cycle_counts_match_the_fast_mode() {
+    // A sample of addressing modes, crossing a page where possible.
+    let programs: [&[Byte]; 12] = [
+        &[0xA9, 0x01],       // LDA #imm
+        &[0x7D, 0xF0, 0x12], // ADC abs,X
+        &[0x91, 0x10],       // STA (zp),Y
+        &[0xB1, 0x10],       // LDA (zp),Y
+        &[0xA1, 0x10],       // LDA (zp,X)
+        &[0x1E, 0xF0, 0x12], // ASL abs,X
+        &[0x0E, 0x00, 0x12], // ASL abs
+        &[0x0A],             // ASL A
+        &[0x48],             // PHA
+        &[0x68],             // PLA
+        &[0x40],             // RTI
+        &[0x6C, 0x00, 0x12], // JMP (ind)
+    ];
+
+    for program in programs {
+        let setup = |cpu: &mut CPU<RecordingBus>| {
+            cpu.registers.x = 0x20;
+            cpu.registers.y = 0x20;
+            cpu.registers.sp = 0xF0;
+            cpu.bus.memory[0x10] = 0xF0;
+            cpu.bus.memory[0x11] = 0x12;
+        };
+
+        let (accurate, _) = run(program, setup);
+
+        let mut fast = cpu(program, false);
+        setup(&mut fast);
+        assert!(fast.step());
+
+        assert_eq!(accurate.cycles, fast.cycles, "program {:02X?}", program);
+    }
+}
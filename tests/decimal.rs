@@ -0,0 +1,244 @@
+use famines::{
+    cpu::{registers::Registers, CpuVariant, CPU},
+    memory::{flat::FlatMemory, Address, Byte},
+};
+
+const C: u8 = Registers::CARRY_FLAG;
+const Z: u8 = Registers::ZERO_FLAG;
+const V: u8 = Registers::OVERFLOW_FLAG;
+const N: u8 = Registers::NEGATIVE_FLAG;
+
+/// Both builds are assembled with as65 to a binary loaded at the `org` of
+/// $0200: cputype = 0 for the 6502 and cputype = 1 for the 65C02.
+const NMOS_DECIMAL_TEST: &str = "res/6502_decimal_test.bin";
+const CMOS_DECIMAL_TEST: &str = "res/65C02_decimal_test.bin";
+const DECIMAL_TEST_START: Address = 0x0200;
+/// Zero page byte that the test leaves at 0 when every result matched.
+const DECIMAL_TEST_ERROR: Address = 0x000B;
+/// The `end_of_test` macro, which is STP on the 65C02.
+const END_OF_TEST: Byte = 0xDB;
+
+fn cpu(variant: CpuVariant) -> CPU<FlatMemory> {
+    let mut cpu = CPU::new(FlatMemory::new());
+    cpu.variant = variant;
+    cpu
+}
+
+/// Runs `opcode #operand` with the decimal flag set and returns A and NVZC.
+fn run(cpu: &mut CPU<FlatMemory>, opcode: Byte, a: Byte, operand: Byte, carry: bool) -> (Byte, Byte) {
+    cpu.bus[0x0400] = opcode;
+    cpu.bus[0x0401] = operand;
+    cpu.registers.pc = 0x0400;
+    cpu.registers.a = a;
+    cpu.registers.flags = Registers::UNUSED_FLAG | Registers::DECIMAL_FLAG;
+    cpu.registers.set_flag(C, carry);
+
+    assert!(cpu.step());
+    (cpu.registers.a, cpu.registers.flags & (N | V | Z | C))
+}
+
+fn adc(cpu: &mut CPU<FlatMemory>, a: Byte, operand: Byte, carry: bool) -> (Byte, Byte) {
+    run(cpu, 0x69, a, operand, carry)
+}
+
+fn sbc(cpu: &mut CPU<FlatMemory>, a: Byte, operand: Byte, carry: bool) -> (Byte, Byte) {
+    run(cpu, 0xE9, a, operand, carry)
+}
+
+fn flags(n: bool, v: bool, z: bool, c: bool) -> Byte {
+    (n as Byte * N) | (v as Byte * V) | (z as Byte * Z) | (c as Byte * C)
+}
+
+/// The predictions of Klaus Dormann's 6502_decimal_test (after Bruce
+/// Clark's tutorial), for every pair of operands and carry in. They stand
+/// in for the test binaries, which run below when present in `res/`.
+fn predict_adc(variant: CpuVariant, n1: Byte, n2: Byte, carry: bool) -> (Byte, Byte) {
+    let (n1, n2, c) = (n1 as i32, n2 as i32, carry as i32);
+
+    // Sequence 1: the accumulator and carry.
+    let mut al = (n1 & 0x0F) + (n2 & 0x0F) + c;
+    if al >= 0x0A {
+        al = ((al + 0x06) & 0x0F) + 0x10;
+    }
+    let mut ar = (n1 & 0xF0) + (n2 & 0xF0) + al;
+    if ar >= 0xA0 {
+        ar += 0x60;
+    }
+    let cf = ar >= 0x100;
+    let ar = (ar & 0xFF) as Byte;
+
+    // Sequence 2: N and V, using signed arithmetic.
+    let sum = (n1 & 0xF0) as u8 as i8 as i32 + (n2 & 0xF0) as u8 as i8 as i32 + al;
+    let vf = !(-128..=127).contains(&sum);
+
+    let (nf, zf) = match variant {
+        CpuVariant::Cmos65C02 => (ar & 0x80 != 0, ar == 0),
+        _ => (sum & 0x80 != 0, (n1 + n2 + c) & 0xFF == 0),
+    };
+
+    (ar, flags(nf, vf, zf, cf))
+}
+
+fn predict_sbc(variant: CpuVariant, n1: Byte, n2: Byte, carry: bool) -> (Byte, Byte) {
+    let (n1, n2, borrow) = (n1 as i32, n2 as i32, !carry as i32);
+
+    let binary = n1 - n2 - borrow;
+    let hb = (binary & 0xFF) as Byte;
+    let cf = binary >= 0;
+    let vf = (n1 ^ n2) & (n1 ^ binary) & 0x80 != 0;
+
+    let mut al = (n1 & 0x0F) - (n2 & 0x0F) - borrow;
+    let ar = match variant {
+        CpuVariant::Cmos65C02 => {
+            // Sequence 4
+            let mut ar = n1 - n2 - borrow;
+            if ar < 0 {
+                ar -= 0x60;
+            }
+            if al < 0 {
+                ar -= 0x06;
+            }
+            ar
+        }
+        _ => {
+            // Sequence 3
+            if al < 0 {
+                al = ((al - 0x06) & 0x0F) - 0x10;
+            }
+            let mut ar = (n1 & 0xF0) - (n2 & 0xF0) + al;
+            if ar < 0 {
+                ar -= 0x60;
+            }
+            ar
+        }
+    };
+    let ar = (ar & 0xFF) as Byte;
+
+    let (nf, zf) = match variant {
+        CpuVariant::Cmos65C02 => (ar & 0x80 != 0, ar == 0),
+        _ => (hb & 0x80 != 0, hb == 0),
+    };
+
+    (ar, flags(nf, vf, zf, cf))
+}
+
+fn check_all(variant: CpuVariant) {
+    let mut cpu = cpu(variant);
+    for n1 in 0..=0xFF {
+        for n2 in 0..=0xFF {
+            for carry in [false, true] {
+                assert_eq!(
+                    adc(&mut cpu, n1, n2, carry),
+                    predict_adc(variant, n1, n2, carry),
+                    "{:?} ADC {:02X} + {:02X} + {}",
+                    variant,
+                    n1,
+                    n2,
+                    carry as u8
+                );
+                assert_eq!(
+                    sbc(&mut cpu, n1, n2, carry),
+                    predict_sbc(variant, n1, n2, carry),
+                    "{:?} SBC {:02X} - {:02X} - {}",
+                    variant,
+                    n1,
+                    n2,
+                    !carry as u8
+                );
+            }
+        }
+    }
+}
+
+/// Runs one build of Klaus Dormann's 6502_decimal_test (after Bruce Clark's
+/// tutorial), which checks ADC and SBC in decimal mode for every pair of
+/// operands and carry in, and returns its ERROR byte.
+fn decimal_test(path: &str, variant: CpuVariant) -> Byte {
+    let image = std::fs::read(path).unwrap_or_else(|error| panic!("{}: {}", path, error));
+
+    let mut cpu = cpu(variant);
+    cpu.bus.load(DECIMAL_TEST_START, &image);
+    cpu.registers.pc = DECIMAL_TEST_START;
+
+    for _ in 0..100_000_000 {
+        let pc = cpu.registers.pc;
+        if cpu.bus[pc as usize] == END_OF_TEST {
+            return cpu.bus[DECIMAL_TEST_ERROR as usize];
+        }
+        assert!(cpu.step(), "{:?} locked up at ${:04X}", variant, pc);
+    }
+
+    panic!("{:?} never reached the end of {}", variant, path);
+}
+
+#[test]
+fn nmos_matches_decimal_test_predictions() {
+    check_all(CpuVariant::Nmos6502);
+}
+
+#[test]
+fn cmos_matches_decimal_test_predictions() {
+    check_all(CpuVariant::Cmos65C02);
+}
+
+#[test]
+#[ignore = "needs res/6502_decimal_test.bin"]
+fn nmos_decimal_test() {
+    assert_eq!(decimal_test(NMOS_DECIMAL_TEST, CpuVariant::Nmos6502), 0);
+}
+
+#[test]
+#[ignore = "needs res/65C02_decimal_test.bin"]
+fn cmos_decimal_test() {
+    assert_eq!(decimal_test(CMOS_DECIMAL_TEST, CpuVariant::Cmos65C02), 0);
+}
+
+#[test]
+fn valid_bcd_results() {
+    // Only A and C are defined on every variant.
+    let cases: [(Byte, Byte, Byte, bool, Byte, bool); 5] = [
+        (0x69, 0x09, 0x01, false, 0x10, false),
+        (0x69, 0x58, 0x46, true, 0x05, true),
+        (0xE9, 0x46, 0x12, true, 0x34, true),
+        (0xE9, 0x40, 0x13, true, 0x27, true),
+        (0xE9, 0x32, 0x02, false, 0x29, true),
+    ];
+
+    for variant in [CpuVariant::Nmos6502, CpuVariant::Cmos65C02] {
+        let mut cpu = cpu(variant);
+        for (opcode, a, operand, carry, result, carry_out) in cases {
+            let (actual, flags) = run(&mut cpu, opcode, a, operand, carry);
+            assert_eq!((actual, flags & C != 0), (result, carry_out));
+        }
+    }
+}
+
+#[test]
+fn nmos_flags_come_from_binary_result() {
+    let mut cpu = cpu(CpuVariant::Nmos6502);
+    // $99 + $01 = $00 with carry, but Z is clear ($9A in binary) and N is set.
+    assert_eq!(adc(&mut cpu, 0x99, 0x01, false), (0x00, N | C));
+    // $00 - $01 = $99 with borrow, and N reflects the binary $FF.
+    assert_eq!(sbc(&mut cpu, 0x00, 0x01, true), (0x99, N));
+
+    let mut cpu = self::cpu(CpuVariant::Cmos65C02);
+    assert_eq!(adc(&mut cpu, 0x99, 0x01, false), (0x00, Z | C));
+    assert_eq!(sbc(&mut cpu, 0x00, 0x01, true), (0x99, N));
+}
+
+#[test]
+fn invalid_bcd_digits_are_adjusted() {
+    for variant in [CpuVariant::Nmos6502, CpuVariant::Cmos65C02] {
+        let mut cpu = cpu(variant);
+        // A low digit above 9 still carries into the high digit.
+        assert_eq!(adc(&mut cpu, 0x0F, 0x01, false), (0x16, 0), "{:?}", variant);
+        assert_eq!(adc(&mut cpu, 0x0A, 0x00, false), (0x10, 0), "{:?}", variant);
+    }
+}
+
+#[test]
+fn ricoh_2a03_ignores_decimal_flag() {
+    let mut cpu = cpu(CpuVariant::Ricoh2A03);
+    assert_eq!(adc(&mut cpu, 0x09, 0x01, false), (0x0A, 0));
+    assert_eq!(sbc(&mut cpu, 0x10, 0x01, true), (0x0F, C));
+}
@@ -0,0 +1,91 @@
+use famines::{
+    cpu::{CpuVariant, CPU},
+    memory::{flat::FlatMemory, Address},
+};
+
+/// Klaus Dormann's 6502_functional_test.bin as prebuilt in the bin_files of
+/// his 6502_65C02_functional_tests repository, with the default options: a
+/// 64KB image loaded at $0000, starting at $0400. The listing shipped next to
+/// that binary puts the success trap at $3469; other builds move it.
+const FUNCTIONAL_TEST: &str = "res/6502_functional_test.bin";
+const FUNCTIONAL_TEST_START: Address = 0x0400;
+const FUNCTIONAL_TEST_SUCCESS: Address = 0x3469;
+
+#[derive(Debug, PartialEq)]
+enum Stop {
+    /// An instruction jumped or branched to itself, which is how the test
+    /// suites trap.
+    Trap(Address),
+    /// The CPU locked up on the instruction at this address (KIL, or STP).
+    Lockup(Address),
+}
+
+/// Steps until the program traps or locks up, or gives up after `max_steps`.
+fn run_until_trap(cpu: &mut CPU<FlatMemory>, max_steps: usize) -> Option<Stop> {
+    for _ in 0..max_steps {
+        let pc = cpu.registers.pc;
+        if !cpu.step() {
+            return Some(Stop::Lockup(pc));
+        }
+        if cpu.registers.pc == pc {
+            return Some(Stop::Trap(pc));
+        }
+    }
+
+    None
+}
+
+#[test]
+fn traps_on_jump_to_self() {
+    // LDX #$05; DEX; BNE -3; JMP $0405
+    let mut memory = FlatMemory::new();
+    memory.load(0x0400, &[0xA2, 0x05, 0xCA, 0xD0, 0xFD, 0x4C, 0x05, 0x04]);
+
+    let mut cpu = CPU::new(memory);
+    cpu.registers.pc = 0x0400;
+
+    assert_eq!(run_until_trap(&mut cpu, 100), Some(Stop::Trap(0x0405)));
+    assert_eq!(cpu.registers.x, 0x00);
+}
+
+#[test]
+fn stops_on_lockup() {
+    // LDX #$05; KIL
+    let mut memory = FlatMemory::new();
+    memory.load(0x0400, &[0xA2, 0x05, 0x02]);
+
+    let mut cpu = CPU::new(memory);
+    cpu.registers.pc = 0x0400;
+
+    assert_eq!(run_until_trap(&mut cpu, 100), Some(Stop::Lockup(0x0402)));
+}
+
+#[test]
+#[ignore = "needs res/6502_functional_test.bin"]
+fn functional_test() {
+    let image = std::fs::read(FUNCTIONAL_TEST).unwrap_or_else(|error| {
+        panic!(
+            "{}: {}, copy bin_files/6502_functional_test.bin from Klaus Dormann's \
+             6502_65C02_functional_tests repository there",
+            FUNCTIONAL_TEST, error
+        )
+    });
+
+    let mut memory = FlatMemory::new();
+    memory.load(0x0000, &image);
+
+    // The suite covers decimal mode, which the 2A03 lacks.
+    let mut cpu = CPU::new(memory);
+    cpu.variant = CpuVariant::Nmos6502;
+    cpu.registers.pc = FUNCTIONAL_TEST_START;
+
+    match run_until_trap(&mut cpu, 100_000_000) {
+        Some(Stop::Trap(FUNCTIONAL_TEST_SUCCESS)) => {}
+        Some(Stop::Trap(trap)) => panic!(
+            "the functional test failed at ${:04X}, see the listing for the test there",
+            trap
+        ),
+        Some(Stop::Lockup(pc)) => panic!("the CPU locked up at ${:04X}", pc),
+        None => panic!("the functional test never trapped"),
+    }
+}
@@ -0,0 +1,128 @@
+use famines::{
+    cpu::{registers::Registers, CPU},
+    memory::{flat::FlatMemory, Byte},
+};
+
+const B: u8 = Registers::BREAK_FLAG;
+const U: u8 = Registers::UNUSED_FLAG;
+const I: u8 = Registers::IRQ_FLAG;
+const C: u8 = Registers::CARRY_FLAG;
+
+/// A CPU running `program` at $0400, with the NMI vector at $9000 and the
+/// IRQ/BRK vector at $8000, each pointing to a NOP.
+fn cpu(program: &[Byte], flags: Byte) -> CPU<FlatMemory> {
+    let mut memory = FlatMemory::new();
+    memory.load(0x0400, program);
+    memory.load(0xFFFA, &[0x00, 0x90, 0x00, 0x04, 0x00, 0x80]);
+    memory[0x8000] = 0xEA;
+    memory[0x9000] = 0xEA;
+
+    let mut cpu = CPU::new(memory);
+    cpu.registers.pc = 0x0400;
+    cpu.registers.sp = 0xFD;
+    cpu.registers.flags = flags;
+    cpu
+}
+
+/// The return address and flags pushed by the last interrupt or BRK.
+fn pushed(cpu: &CPU<FlatMemory>) -> (u16, Byte) {
+    let bus = &cpu.bus;
+    (u16::from_le_bytes([bus[0x01FC], bus[0x01FD]]), bus[0x01FB])
+}
+
+#[test]
+fn brk_skips_padding_byte_and_pushes_b() {
+    for cycle_accurate in [false, true] {
+        // BRK; padding
+        let mut cpu = cpu(&[0x00, 0xFF], C);
+        cpu.cycle_accurate = cycle_accurate;
+
+        assert!(cpu.step());
+        assert_eq!(cpu.registers.pc, 0x8000);
+        assert_eq!(pushed(&cpu), (0x0402, C | B | U));
+        assert_eq!(cpu.registers.sp, 0xFA);
+        assert!(cpu.registers.get_flag(I));
+        assert_eq!(cpu.cycles, 7);
+    }
+}
+
+#[test]
+fn php_pushes_b_and_unused() {
+    // PHP
+    let mut cpu = cpu(&[0x08], C);
+
+    assert!(cpu.step());
+    assert_eq!(cpu.bus[0x01FD], C | B | U);
+}
+
+#[test]
+fn irq_pushes_current_pc_without_b() {
+    for cycle_accurate in [false, true] {
+        // NOP
+        let mut cpu = cpu(&[0xEA], C);
+        cpu.cycle_accurate = cycle_accurate;
+        cpu.irq_line = true;
+
+        assert!(cpu.step());
+        assert_eq!(cpu.registers.pc, 0x8000);
+        assert_eq!(pushed(&cpu), (0x0400, C | U));
+        assert!(cpu.registers.get_flag(I));
+        assert_eq!(cpu.cycles, 7);
+    }
+}
+
+#[test]
+fn irq_is_masked_by_i_flag() {
+    // NOP
+    let mut cpu = cpu(&[0xEA], I);
+    cpu.irq_line = true;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.pc, 0x0401);
+    assert_eq!(cpu.cycles, 2);
+}
+
+#[test]
+fn nmi_ignores_i_flag() {
+    for cycle_accurate in [false, true] {
+        // NOP
+        let mut cpu = cpu(&[0xEA], I);
+        cpu.cycle_accurate = cycle_accurate;
+        cpu.nmi_pending = true;
+
+        assert!(cpu.step());
+        assert_eq!(cpu.registers.pc, 0x9000);
+        assert_eq!(pushed(&cpu), (0x0400, I | U));
+        assert!(!cpu.nmi_pending);
+        assert_eq!(cpu.cycles, 7);
+    }
+}
+
+#[test]
+fn nmi_takes_priority_over_irq() {
+    // NOP
+    let mut cpu = cpu(&[0xEA], 0);
+    cpu.nmi_pending = true;
+    cpu.irq_line = true;
+
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.pc, 0x9000);
+
+    // The NMI set I, so the IRQ stays pending and the handler runs.
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.pc, 0x9001);
+}
+
+#[test]
+fn rti_restores_pc_and_flags() {
+    // BRK; padding; then RTI in the handler
+    let mut cpu = cpu(&[0x00, 0xFF], C);
+    cpu.bus[0x8000] = 0x40;
+
+    assert!(cpu.step());
+    assert!(cpu.step());
+    assert_eq!(cpu.registers.pc, 0x0402);
+    assert_eq!(cpu.registers.flags, C | U);
+    assert_eq!(cpu.registers.sp, 0xFD);
+    assert_eq!(cpu.cycles, 7 + 6);
+}
@@ -0,0 +1,182 @@
+use famines::{
+    cartridge::{Cartridge, Mirroring},
+    memory::Memory,
+};
+
+/// `count` banks of `size` bytes, each starting with its own index. The rest
+/// is $FF, so that bus conflicts leave most written values alone.
+fn banks(count: usize, size: usize) -> Vec<u8> {
+    let mut data = vec![0xFF; count * size];
+    for bank in 0..count {
+        data[bank * size] = bank as u8;
+    }
+    data
+}
+
+/// Builds an image with an iNES header, or a NES 2.0 one when a submapper is given.
+fn cartridge(mapper: u8, submapper: Option<u8>, prg: &[u8], chr: &[u8]) -> Cartridge {
+    let mut raw = vec![
+        b'N',
+        b'E',
+        b'S',
+        0x1A,
+        (prg.len() / 0x4000) as u8,
+        (chr.len() / 0x2000) as u8,
+        mapper << 4,
+        mapper & 0xF0,
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+    ];
+    if let Some(submapper) = submapper {
+        raw[7] |= 0x08;
+        raw[8] = submapper << 4;
+        raw[11] = if chr.is_empty() { 0x07 } else { 0 };
+    }
+    raw.extend_from_slice(prg);
+    raw.extend_from_slice(chr);
+    Cartridge::new(&raw).unwrap()
+}
+
+#[test]
+fn nrom_mirrors_16kb_prg() {
+    let mut prg = banks(1, 0x4000);
+    prg[0x0123] = 0x5A;
+    let mut cartridge = cartridge(0, None, &prg, &banks(1, 0x2000));
+
+    assert_eq!(cartridge.read_byte(0x8123), 0x5A);
+    assert_eq!(cartridge.read_byte(0xC123), 0x5A);
+}
+
+#[test]
+fn nrom_maps_32kb_prg_and_ram() {
+    let mut chr = banks(1, 0x2000);
+    chr[0x1FFF] = 0x33;
+    let mut cartridge = cartridge(0, None, &banks(2, 0x4000), &chr);
+    assert_eq!(cartridge.read_byte(0x8000), 0);
+    assert_eq!(cartridge.read_byte(0xC000), 1);
+    assert_eq!(cartridge.read_chr(0x1FFF), 0x33);
+
+    // ROM writes go nowhere, PRG-RAM at $6000 keeps them.
+    cartridge.write_byte(0x8000, 0x12);
+    cartridge.write_byte(0x6000, 0x34);
+    cartridge.write_byte(0x7FFF, 0x56);
+    assert_eq!(cartridge.read_byte(0x8000), 0);
+    assert_eq!(cartridge.read_byte(0x6000), 0x34);
+    assert_eq!(cartridge.read_byte(0x7FFF), 0x56);
+}
+
+/// UxROM with 8 banks, the fixed last one holding $03 at $C100.
+fn uxrom(submapper: Option<u8>) -> Cartridge {
+    let mut prg = banks(8, 0x4000);
+    prg[7 * 0x4000 + 0x100] = 0x03;
+    cartridge(2, submapper, &prg, &[])
+}
+
+#[test]
+fn uxrom_switches_16kb_at_8000() {
+    let mut cartridge = uxrom(None);
+    assert_eq!(cartridge.read_byte(0x8000), 0);
+    assert_eq!(cartridge.read_byte(0xC000), 7);
+
+    cartridge.write_byte(0xFFF0, 0x05);
+    assert_eq!(cartridge.read_byte(0x8000), 5);
+    assert_eq!(cartridge.read_byte(0xBFFF), 0xFF);
+    assert_eq!(cartridge.read_byte(0xC000), 7);
+}
+
+#[test]
+fn uxrom_bus_conflicts_follow_the_submapper() {
+    // (submapper, bank selected by writing $06 over a ROM byte of $03)
+    let boards = [(None, 2), (Some(0), 2), (Some(1), 6), (Some(2), 2)];
+
+    for (submapper, bank) in boards {
+        let mut cartridge = uxrom(submapper);
+        cartridge.write_byte(0xC100, 0x06);
+        assert_eq!(
+            cartridge.read_byte(0x8000),
+            bank,
+            "submapper {:?}",
+            submapper
+        );
+    }
+}
+
+#[test]
+fn cnrom_switches_8kb_chr_with_bus_conflicts() {
+    let mut prg = banks(2, 0x4000);
+    prg[0x4100] = 0x01;
+    let mut cartridge = cartridge(3, None, &prg, &banks(4, 0x2000));
+    assert_eq!(cartridge.read_chr(0x0000), 0);
+
+    cartridge.write_byte(0x8001, 0x02);
+    assert_eq!(cartridge.read_chr(0x0000), 2);
+    assert_eq!(cartridge.read_chr(0x1FFF), 0xFF);
+    assert_eq!(cartridge.read_byte(0xC000), 1);
+
+    cartridge.write_byte(0xC100, 0x03);
+    assert_eq!(cartridge.read_chr(0x0000), 1);
+}
+
+#[test]
+fn axrom_switches_32kb_and_the_nametable() {
+    let mut cartridge = cartridge(7, None, &banks(4, 0x8000), &[]);
+    assert_eq!(cartridge.read_byte(0x8000), 0);
+    assert_eq!(cartridge.mirroring(), Mirroring::SingleScreenA);
+
+    cartridge.write_byte(0x8001, 0x12);
+    assert_eq!(cartridge.read_byte(0x8000), 2);
+    assert_eq!(cartridge.read_byte(0xFFFF), 0xFF);
+    assert_eq!(cartridge.mirroring(), Mirroring::SingleScreenB);
+
+    cartridge.write_byte(0x8001, 0x03);
+    assert_eq!(cartridge.read_byte(0x8000), 3);
+    assert_eq!(cartridge.mirroring(), Mirroring::SingleScreenA);
+}
+
+#[test]
+fn axrom_bus_conflicts_follow_the_submapper() {
+    // (submapper, bank selected by writing $13 over a ROM byte of $11)
+    let boards = [(None, 3), (Some(0), 3), (Some(1), 3), (Some(2), 1)];
+
+    for (submapper, bank) in boards {
+        let mut prg = banks(4, 0x8000);
+        prg[0x0100] = 0x11;
+        let mut cartridge = cartridge(7, submapper, &prg, &[]);
+
+        cartridge.write_byte(0x8100, 0x13);
+        assert_eq!(
+            cartridge.read_byte(0x8000),
+            bank,
+            "submapper {:?}",
+            submapper
+        );
+        assert_eq!(cartridge.mirroring(), Mirroring::SingleScreenB);
+    }
+}
+
+#[test]
+fn gxrom_splits_prg_and_chr_bits() {
+    let mut prg = banks(4, 0x8000);
+    prg[0x0100] = 0x30;
+    let mut cartridge = cartridge(66, None, &prg, &banks(4, 0x2000));
+
+    cartridge.write_byte(0x8001, 0x21);
+    assert_eq!(cartridge.read_byte(0x8000), 2);
+    assert_eq!(cartridge.read_chr(0x0000), 1);
+
+    // Bits 2-3 and 6-7 are not connected.
+    cartridge.write_byte(0x8001, 0xCE);
+    assert_eq!(cartridge.read_byte(0x8000), 0);
+    assert_eq!(cartridge.read_chr(0x0000), 2);
+
+    // Bus conflict with the $30 at $8100 of bank 0.
+    cartridge.write_byte(0x8100, 0x33);
+    assert_eq!(cartridge.read_byte(0x8000), 3);
+    assert_eq!(cartridge.read_chr(0x0000), 0);
+}
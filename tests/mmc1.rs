@@ -0,0 +1,169 @@
+use famines::{
+    cartridge::Mirroring,
+    mapper::{mmc1::MMC1, Mapper, Mapping},
+};
+
+const PRG_BANK: usize = 0x4000;
+const CHR_BANK: usize = 0x1000;
+const RAM_BANK: usize = 0x2000;
+
+/// Loads a register through the serial port, least significant bit first.
+fn write(mmc1: &mut MMC1, address: u16, value: u8) {
+    for bit in 0..5 {
+        mmc1.cpu_write(address, (value >> bit) & 0x01);
+    }
+}
+
+fn prg_bank(mmc1: &mut MMC1, address: u16) -> usize {
+    match mmc1.cpu_read(address) {
+        Mapping::Prg(offset) => offset / PRG_BANK,
+        mapping => panic!("${:04X} mapped to {:?}", address, mapping),
+    }
+}
+
+fn chr_bank(mmc1: &mut MMC1, address: u16) -> usize {
+    mmc1.ppu_read(address) / CHR_BANK
+}
+
+#[test]
+fn fifth_write_loads_the_register() {
+    let mut mmc1 = MMC1::new(0x40000, 0x2000, 0x2000);
+
+    for bit in [1, 0, 1, 0] {
+        mmc1.cpu_write(0xE000, bit);
+        assert_eq!(prg_bank(&mut mmc1, 0x8000), 0);
+    }
+    mmc1.cpu_write(0xE000, 0);
+    assert_eq!(prg_bank(&mut mmc1, 0x8000), 5);
+
+    // Only the address of the fifth write selects the register.
+    for bit in [0, 1, 1, 1] {
+        mmc1.cpu_write(0xE000, bit);
+    }
+    mmc1.cpu_write(0x8000, 0);
+    assert_eq!(mmc1.mirroring(), Some(Mirroring::Vertical));
+    assert_eq!(prg_bank(&mut mmc1, 0x8000), 5);
+}
+
+#[test]
+fn reset_bit_clears_the_shift_register_and_fixes_the_last_bank() {
+    let mut mmc1 = MMC1::new(0x40000, 0x2000, 0x2000);
+    write(&mut mmc1, 0x8000, 0b0_0000);
+    write(&mut mmc1, 0xE000, 3);
+    assert_eq!(prg_bank(&mut mmc1, 0xC000), 3);
+
+    mmc1.cpu_write(0xE000, 0x01);
+    mmc1.cpu_write(0xE000, 0x01);
+    mmc1.cpu_write(0x8000, 0x80);
+    assert_eq!(prg_bank(&mut mmc1, 0x8000), 3);
+    assert_eq!(prg_bank(&mut mmc1, 0xC000), 15);
+
+    // The two bits written before the reset are gone.
+    write(&mut mmc1, 0xE000, 6);
+    assert_eq!(prg_bank(&mut mmc1, 0x8000), 6);
+}
+
+#[test]
+fn prg_banking_modes() {
+    let mut mmc1 = MMC1::new(0x40000, 0x2000, 0x2000);
+    write(&mut mmc1, 0xE000, 5);
+
+    // (control, bank at $8000, bank at $C000)
+    let modes = [
+        (0b0_0000, 4, 5),
+        (0b0_0100, 4, 5),
+        (0b0_1000, 0, 5),
+        (0b0_1100, 5, 15),
+    ];
+    for (control, low, high) in modes {
+        write(&mut mmc1, 0x8000, control);
+        assert_eq!(prg_bank(&mut mmc1, 0x8000), low, "control {:05b}", control);
+        assert_eq!(prg_bank(&mut mmc1, 0xBFFF), low, "control {:05b}", control);
+        assert_eq!(prg_bank(&mut mmc1, 0xC000), high, "control {:05b}", control);
+        assert_eq!(prg_bank(&mut mmc1, 0xFFFF), high, "control {:05b}", control);
+    }
+}
+
+#[test]
+fn chr_banking_modes() {
+    let mut mmc1 = MMC1::new(0x40000, 0x20000, 0x2000);
+    write(&mut mmc1, 0xA000, 5);
+    write(&mut mmc1, 0xC000, 9);
+
+    // 8KB mode ignores the low bit and the second register.
+    write(&mut mmc1, 0x8000, 0b0_1100);
+    assert_eq!(chr_bank(&mut mmc1, 0x0000), 4);
+    assert_eq!(chr_bank(&mut mmc1, 0x1000), 5);
+
+    write(&mut mmc1, 0x8000, 0b1_1100);
+    assert_eq!(chr_bank(&mut mmc1, 0x0000), 5);
+    assert_eq!(chr_bank(&mut mmc1, 0x0FFF), 5);
+    assert_eq!(chr_bank(&mut mmc1, 0x1000), 9);
+    assert_eq!(chr_bank(&mut mmc1, 0x1FFF), 9);
+}
+
+#[test]
+fn mirroring_modes() {
+    let mut mmc1 = MMC1::new(0x40000, 0x2000, 0x2000);
+    let modes = [
+        Mirroring::SingleScreenA,
+        Mirroring::SingleScreenB,
+        Mirroring::Vertical,
+        Mirroring::Horizontal,
+    ];
+    for (control, mirroring) in modes.into_iter().enumerate() {
+        write(&mut mmc1, 0x8000, 0b0_1100 | control as u8);
+        assert_eq!(mmc1.mirroring(), Some(mirroring));
+    }
+}
+
+#[test]
+fn prg_ram_disable_bit() {
+    let mut mmc1 = MMC1::new(0x40000, 0x2000, 0x2000);
+    assert_eq!(mmc1.cpu_read(0x6123), Mapping::Ram(0x0123));
+    assert_eq!(mmc1.cpu_write(0x7FFF, 0x55), Mapping::Ram(0x1FFF));
+
+    write(&mut mmc1, 0xE000, 0x10);
+    assert_eq!(mmc1.cpu_read(0x6123), Mapping::None);
+    assert_eq!(mmc1.cpu_write(0x7FFF, 0x55), Mapping::None);
+}
+
+#[test]
+fn surom_selects_the_prg_half_with_chr_bit_4() {
+    let mut mmc1 = MMC1::new(0x80000, 0x2000, 0x2000);
+    write(&mut mmc1, 0xE000, 2);
+    assert_eq!(prg_bank(&mut mmc1, 0x8000), 2);
+    assert_eq!(prg_bank(&mut mmc1, 0xC000), 15);
+
+    write(&mut mmc1, 0xA000, 0x10);
+    assert_eq!(prg_bank(&mut mmc1, 0x8000), 16 + 2);
+    assert_eq!(prg_bank(&mut mmc1, 0xC000), 16 + 15);
+
+    // Boards up to 256KB use that bit for CHR banking only.
+    let mut mmc1 = MMC1::new(0x40000, 0x2000, 0x2000);
+    write(&mut mmc1, 0xA000, 0x10);
+    assert_eq!(prg_bank(&mut mmc1, 0xC000), 15);
+}
+
+#[test]
+fn prg_ram_banking_follows_the_board() {
+    // (PRG-RAM size, CHR bank 0, PRG-RAM bank)
+    let boards = [
+        (0x2000, 0b0_1100, 0),
+        (0x4000, 0b0_0100, 0),
+        (0x4000, 0b0_1000, 1),
+        (0x8000, 0b0_0100, 1),
+        (0x8000, 0b0_1100, 3),
+    ];
+    for (size, chr_bank, bank) in boards {
+        let mut mmc1 = MMC1::new(0x40000, 0x2000, size);
+        write(&mut mmc1, 0xA000, chr_bank);
+        assert_eq!(
+            mmc1.cpu_read(0x6010),
+            Mapping::Ram(bank * RAM_BANK + 0x10),
+            "{}KB with CHR bank {:05b}",
+            size / 1024,
+            chr_bank
+        );
+    }
+}
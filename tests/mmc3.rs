@@ -0,0 +1,208 @@
+use famines::{
+    cartridge::{Cartridge, Mirroring},
+    mapper::{mmc3::MMC3, Mapper, Mapping},
+    memory::Memory,
+    ppu::PPU,
+};
+
+const PRG_BANK: usize = 0x2000;
+const CHR_BANK: usize = 0x0400;
+
+fn mmc3() -> MMC3 {
+    MMC3::new(0x80000, 0x40000, Mirroring::Vertical)
+}
+
+fn prg_bank(mmc3: &mut MMC3, address: u16) -> usize {
+    match mmc3.cpu_read(address) {
+        Mapping::Prg(offset) => offset / PRG_BANK,
+        mapping => panic!("${:04X} mapped to {:?}", address, mapping),
+    }
+}
+
+fn chr_banks(mmc3: &mut MMC3) -> [usize; 8] {
+    [0, 1, 2, 3, 4, 5, 6, 7].map(|bank| mmc3.ppu_read(bank * 0x400) / CHR_BANK)
+}
+
+fn set_banks(mmc3: &mut MMC3, banks: [u8; 8]) {
+    for (register, bank) in banks.into_iter().enumerate() {
+        mmc3.cpu_write(0x8000, register as u8);
+        mmc3.cpu_write(0x8001, bank);
+    }
+}
+
+/// One scanline's worth of PPU fetches: background from $0000 for most of
+/// the line, then sprites from $1000, which gives a single A12 rising edge.
+fn scanline(mmc3: &mut MMC3) {
+    mmc3.ppu_read(0x0000);
+    for _ in 0..256 {
+        mmc3.ppu_tick();
+    }
+    mmc3.ppu_read(0x1000);
+    for _ in 0..85 {
+        mmc3.ppu_tick();
+    }
+}
+
+/// Sets the IRQ latch, reloads the counter and enables the IRQ.
+fn arm(mmc3: &mut MMC3, latch: u8) {
+    mmc3.cpu_write(0xC000, latch);
+    mmc3.cpu_write(0xC001, 0);
+    mmc3.cpu_write(0xE001, 0);
+}
+
+#[test]
+fn bank_registers() {
+    let mut mmc3 = mmc3();
+    set_banks(&mut mmc3, [0x11, 0x20, 0x30, 0x31, 0x32, 0x33, 0x05, 0x07]);
+
+    assert_eq!(
+        chr_banks(&mut mmc3),
+        [0x10, 0x11, 0x20, 0x21, 0x30, 0x31, 0x32, 0x33]
+    );
+    assert_eq!(prg_bank(&mut mmc3, 0x8000), 5);
+    assert_eq!(prg_bank(&mut mmc3, 0xA000), 7);
+    assert_eq!(prg_bank(&mut mmc3, 0xC000), 62);
+    assert_eq!(prg_bank(&mut mmc3, 0xE000), 63);
+}
+
+#[test]
+fn prg_mode_swaps_8000_and_c000() {
+    let mut mmc3 = mmc3();
+    set_banks(&mut mmc3, [0, 0, 0, 0, 0, 0, 0x05, 0x07]);
+    mmc3.cpu_write(0x8000, 0x40);
+
+    assert_eq!(prg_bank(&mut mmc3, 0x8000), 62);
+    assert_eq!(prg_bank(&mut mmc3, 0xA000), 7);
+    assert_eq!(prg_bank(&mut mmc3, 0xC000), 5);
+    assert_eq!(prg_bank(&mut mmc3, 0xE000), 63);
+}
+
+#[test]
+fn chr_inversion_swaps_pattern_tables() {
+    let mut mmc3 = mmc3();
+    set_banks(&mut mmc3, [0x11, 0x20, 0x30, 0x31, 0x32, 0x33, 0, 0]);
+    mmc3.cpu_write(0x8000, 0x80);
+
+    assert_eq!(
+        chr_banks(&mut mmc3),
+        [0x30, 0x31, 0x32, 0x33, 0x10, 0x11, 0x20, 0x21]
+    );
+}
+
+#[test]
+fn irq_fires_after_latch_plus_one_scanlines() {
+    let mut mmc3 = mmc3();
+    arm(&mut mmc3, 3);
+
+    // The first clock reloads the counter, the next three count it down.
+    for _ in 0..3 {
+        scanline(&mut mmc3);
+        assert!(!mmc3.irq());
+    }
+    scanline(&mut mmc3);
+    assert!(mmc3.irq());
+}
+
+#[test]
+fn zero_latch_fires_on_every_scanline() {
+    let mut mmc3 = mmc3();
+    arm(&mut mmc3, 0);
+
+    for _ in 0..3 {
+        scanline(&mut mmc3);
+        assert!(mmc3.irq());
+        mmc3.cpu_write(0xE000, 0);
+        mmc3.cpu_write(0xE001, 0);
+    }
+}
+
+#[test]
+fn e000_acknowledges_and_disables() {
+    let mut mmc3 = mmc3();
+    arm(&mut mmc3, 1);
+    scanline(&mut mmc3);
+    scanline(&mut mmc3);
+    assert!(mmc3.irq());
+
+    mmc3.cpu_write(0xE000, 0);
+    assert!(!mmc3.irq());
+
+    // The counter keeps running while disabled, reloading to 1 and reaching 0.
+    scanline(&mut mmc3);
+    scanline(&mut mmc3);
+    assert!(!mmc3.irq());
+
+    mmc3.cpu_write(0xE001, 0);
+    scanline(&mut mmc3);
+    scanline(&mut mmc3);
+    assert!(mmc3.irq());
+}
+
+#[test]
+fn c001_reloads_on_the_next_clock() {
+    let mut mmc3 = mmc3();
+    arm(&mut mmc3, 2);
+    scanline(&mut mmc3);
+    scanline(&mut mmc3);
+
+    // Counter is at 1: reloading puts it back to 2 instead of reaching 0.
+    mmc3.cpu_write(0xC001, 0);
+    scanline(&mut mmc3);
+    assert!(!mmc3.irq());
+    scanline(&mut mmc3);
+    scanline(&mut mmc3);
+    assert!(mmc3.irq());
+}
+
+#[test]
+fn a12_must_stay_low_before_an_edge_counts() {
+    let mut mmc3 = mmc3();
+    arm(&mut mmc3, 1);
+    scanline(&mut mmc3);
+
+    // 8x16 sprites alternating pattern tables, or $2007 accesses, toggle A12
+    // a few dots apart. None of those edges clock the counter.
+    for _ in 0..8 {
+        mmc3.ppu_read(0x0000);
+        mmc3.ppu_tick();
+        mmc3.ppu_tick();
+        mmc3.ppu_read(0x1000);
+    }
+    assert!(!mmc3.irq());
+
+    scanline(&mut mmc3);
+    assert!(mmc3.irq());
+}
+
+#[test]
+fn irq_on_the_expected_scanline_while_rendering() {
+    // MMC3 with 8KB of CHR-RAM.
+    let mut raw = vec![
+        b'N', b'E', b'S', 0x1A, 2, 0, 0x40, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    raw.resize(16 + 2 * 16384, 0);
+    let mut cartridge = Cartridge::new(&raw).unwrap();
+    let mut ppu = PPU::new();
+
+    cartridge.write_byte(0xC000, 20);
+    cartridge.write_byte(0xC001, 0);
+    cartridge.write_byte(0xE001, 0);
+
+    // Background from $0000 and 8x8 sprites from $1000.
+    ppu.write_register(0x2000, 0x08, &mut cartridge);
+    ppu.write_register(0x2001, 0x18, &mut cartridge);
+
+    // Line 0 reloads the counter and lines 1-20 count it down, at the
+    // sprite fetches from dot 257.
+    while (ppu.scanline, ppu.dot) != (20, 257) {
+        ppu.tick(1, &mut cartridge);
+        assert!(
+            !cartridge.irq(),
+            "early IRQ at {}, {}",
+            ppu.scanline,
+            ppu.dot
+        );
+    }
+    ppu.tick(1, &mut cartridge);
+    assert!(cartridge.irq());
+}
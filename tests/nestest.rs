@@ -0,0 +1,85 @@
+use famines::{bus::Bus, cartridge::Cartridge, cpu::CPU, memory::Memory};
+
+/// Decodes the UTF-16 (little endian, with BOM) golden log into its lines.
+fn golden_log() -> Vec<String> {
+    let raw = std::fs::read("res/result.txt").unwrap();
+    let units: Vec<u16> = raw
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .skip_while(|&unit| unit == 0xFEFF)
+        .collect();
+
+    String::from_utf16(&units)
+        .unwrap()
+        .lines()
+        .map(|line| line.trim_end().to_string())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+fn run_nestest(cycle_accurate: bool) {
+    let bytes = std::fs::read("res/nestest.nes").unwrap();
+    let cartridge = Cartridge::new(&bytes).unwrap();
+
+    let mut cpu = CPU::new(Bus::new(cartridge));
+    cpu.cycle_accurate = cycle_accurate;
+    cpu.reset();
+    cpu.registers.pc = 0xC000; // Automation mode.
+
+    let expected = golden_log();
+    assert!(!expected.is_empty());
+
+    let mut history: Vec<String> = Vec::with_capacity(expected.len());
+    for (index, expected) in expected.iter().enumerate() {
+        let actual = cpu.trace_line();
+        if &actual != expected {
+            let context = history[history.len().saturating_sub(5)..].join("\n           ");
+            panic!(
+                "nestest diverged from the golden log at line {}\n\
+                 previous:  {}\n\
+                 expected:  {}\n\
+                 actual:    {}",
+                index + 1,
+                context,
+                expected,
+                actual,
+            );
+        }
+
+        history.push(actual);
+        assert!(cpu.step(), "CPU halted at line {}: {}", index + 1, expected);
+    }
+}
+
+#[test]
+fn nestest_matches_golden_log() {
+    run_nestest(false);
+}
+
+#[test]
+fn nestest_matches_golden_log_cycle_accurate() {
+    run_nestest(true);
+}
+
+#[test]
+fn nestest_reports_no_errors() {
+    let bytes = std::fs::read("res/nestest.nes").unwrap();
+    let cartridge = Cartridge::new(&bytes).unwrap();
+
+    let mut cpu = CPU::new(Bus::new(cartridge));
+    cpu.reset();
+    cpu.registers.pc = 0xC000;
+
+    // The automation mode returns to $C66E once all tests have run, with the
+    // error codes of the official and unofficial opcode tests in $02 and $03.
+    for _ in 0..10_000 {
+        if cpu.registers.pc == 0xC66E {
+            break;
+        }
+        assert!(cpu.step());
+    }
+
+    assert_eq!(cpu.registers.pc, 0xC66E);
+    assert_eq!(cpu.bus.read_byte(0x0002), 0x00, "official opcodes failed");
+    assert_eq!(cpu.bus.read_byte(0x0003), 0x00, "unofficial opcodes failed");
+}
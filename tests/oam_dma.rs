@@ -0,0 +1,52 @@
+use famines::{bus::Bus, cartridge::Cartridge, cpu::CPU, memory::Memory};
+
+fn cpu() -> CPU<Bus> {
+    let mut raw = vec![b'N', b'E', b'S', 0x1A, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    raw.resize(16 + 16384 + 8192, 0);
+    let mut cpu = CPU::new(Bus::new(Cartridge::new(&raw).unwrap()));
+    for offset in 0..0x100 {
+        cpu.write_byte(0x0200 + offset, offset as u8 ^ 0xA5);
+    }
+    cpu
+}
+
+#[test]
+fn copies_page_into_oam() {
+    let mut cpu = cpu();
+    cpu.write_byte(0x2003, 0x10); // DMA starts at OAMADDR and wraps.
+    cpu.write_byte(0x4014, 0x02);
+
+    for offset in 0..0x100 {
+        assert_eq!(cpu.bus.ppu.oam[(offset + 0x10) & 0xFF], offset as u8 ^ 0xA5);
+    }
+    assert_eq!(cpu.bus.ppu.registers.oam_address, 0x10);
+}
+
+#[test]
+fn stalls_the_cpu() {
+    // LDA #$02; STA $4014 ends on an even cycle, the zero page
+    // LDA $02 variant on an odd one.
+    let programs: [(&[u8], usize, usize); 2] = [
+        (&[0xA9, 0x02, 0x8D, 0x14, 0x40], 2 + 4, 513),
+        (&[0xA5, 0x02, 0x8D, 0x14, 0x40], 3 + 4, 514),
+    ];
+
+    for (program, cycles, stall) in programs {
+        let mut cpu = cpu();
+        cpu.write_byte(0x0002, 0x02);
+        for (offset, &byte) in program.iter().enumerate() {
+            cpu.write_byte(0x0300 + offset as u16, byte);
+        }
+        cpu.registers.pc = 0x0300;
+        cpu.registers.flags |= 0x04; // Keep the APU frame IRQ out of the way.
+
+        cpu.step();
+        cpu.step();
+        assert_eq!(cpu.cycles, cycles + stall);
+        assert_eq!(cpu.bus.cycles, cpu.cycles);
+        assert_eq!(cpu.bus.ppu.oam[0], 0xA5);
+
+        // The PPU ran through the stall as well.
+        assert_eq!(cpu.bus.ppu.scanline * 341 + cpu.bus.ppu.dot, (cycles + stall) * 3);
+    }
+}
@@ -0,0 +1,174 @@
+use famines::{
+    bus::{Bus, CpuBus},
+    cartridge::Cartridge,
+    memory::Memory,
+    ppu::{registers::Registers, PPU},
+};
+
+fn cartridge(flags_6: u8) -> Cartridge {
+    let mut raw = vec![
+        b'N', b'E', b'S', 0x1A, 1, 1, flags_6, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    raw.resize(16 + 16384 + 8192, 0);
+    Cartridge::new(&raw).unwrap()
+}
+
+fn write(ppu: &mut PPU, cartridge: &mut Cartridge, address: u16, value: u8) {
+    ppu.write_register(0x2006, (address >> 8) as u8, cartridge);
+    ppu.write_register(0x2006, address as u8, cartridge);
+    ppu.write_register(0x2007, value, cartridge);
+}
+
+fn read(ppu: &mut PPU, cartridge: &mut Cartridge, address: u16) -> u8 {
+    ppu.write_register(0x2006, (address >> 8) as u8, cartridge);
+    ppu.write_register(0x2006, address as u8, cartridge);
+    ppu.read_register(0x2007, cartridge); // Fill the read buffer.
+    ppu.read_register(0x2007, cartridge)
+}
+
+/// Writes a distinct byte to each nametable and returns what each one reads back.
+fn nametables(cartridge: &mut Cartridge) -> [u8; 4] {
+    let mut ppu = PPU::new();
+    for table in 0..4 {
+        write(&mut ppu, cartridge, 0x2000 + table * 0x400, table as u8 + 1);
+    }
+    [0x2000, 0x2400, 0x2800, 0x2C00].map(|address| read(&mut ppu, cartridge, address))
+}
+
+#[test]
+fn horizontal_mirroring() {
+    assert_eq!(nametables(&mut cartridge(0b0000)), [2, 2, 4, 4]);
+}
+
+#[test]
+fn vertical_mirroring() {
+    assert_eq!(nametables(&mut cartridge(0b0001)), [3, 4, 3, 4]);
+}
+
+#[test]
+fn four_screen_vram() {
+    let mut cartridge = cartridge(0b1000);
+    assert_eq!(cartridge.vram.len(), 0x1000);
+    assert_eq!(nametables(&mut cartridge), [1, 2, 3, 4]);
+}
+
+#[test]
+fn single_screen_mirroring_follows_the_mapper() {
+    // AxROM selects the single-screen page with bit 4 of its bank register.
+    let mut raw = vec![
+        b'N', b'E', b'S', 0x1A, 2, 0, 0x70, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    raw.resize(16 + 2 * 16384, 0xFF);
+    let mut cartridge = Cartridge::new(&raw).unwrap();
+    let mut ppu = PPU::new();
+
+    cartridge.write_byte(0x8000, 0x00);
+    write(&mut ppu, &mut cartridge, 0x2C00, 0xAA);
+    cartridge.write_byte(0x8000, 0x10);
+    write(&mut ppu, &mut cartridge, 0x2000, 0xBB);
+
+    assert_eq!(read(&mut ppu, &mut cartridge, 0x2400), 0xBB);
+    cartridge.write_byte(0x8000, 0x00);
+    assert_eq!(read(&mut ppu, &mut cartridge, 0x2800), 0xAA);
+    assert_eq!(ppu.nametables.ciram[0x000], 0xAA);
+    assert_eq!(ppu.nametables.ciram[0x400], 0xBB);
+}
+
+#[test]
+fn upper_nametable_mirror() {
+    let mut cartridge = cartridge(0b0001);
+    let mut ppu = PPU::new();
+    write(&mut ppu, &mut cartridge, 0x3123, 0x5A);
+    assert_eq!(read(&mut ppu, &mut cartridge, 0x2123), 0x5A);
+}
+
+#[test]
+fn ctrl_sets_the_nametable_bits_of_t() {
+    let mut cartridge = cartridge(0);
+    let mut ppu = PPU::new();
+    ppu.registers.t = 0x7FFF;
+
+    ppu.write_register(0x2000, 0x02, &mut cartridge);
+    assert_eq!(ppu.registers.t, 0x7BFF);
+    ppu.write_register(0x2000, 0x01, &mut cartridge);
+    assert_eq!(ppu.registers.t, 0x77FF);
+}
+
+#[test]
+fn scroll_writes_fill_t_and_fine_x() {
+    let mut cartridge = cartridge(0);
+    let mut ppu = PPU::new();
+
+    // Coarse X 15 and fine X 5, then coarse Y 11 and fine Y 6.
+    ppu.write_register(0x2005, 0x7D, &mut cartridge);
+    assert_eq!((ppu.registers.t, ppu.registers.x), (0x000F, 5));
+    assert!(ppu.registers.w);
+
+    ppu.write_register(0x2005, 0x5E, &mut cartridge);
+    assert_eq!((ppu.registers.t, ppu.registers.x), (0x616F, 5));
+    assert!(!ppu.registers.w);
+    assert_eq!(ppu.registers.v, 0x0000);
+}
+
+#[test]
+fn address_writes_fill_t_then_copy_it_to_v() {
+    let mut cartridge = cartridge(0);
+    let mut ppu = PPU::new();
+    ppu.registers.t = 0x7FFF;
+
+    // The high byte only has 6 bits, and the write clears bit 14.
+    ppu.write_register(0x2006, 0xFF, &mut cartridge);
+    assert_eq!(ppu.registers.t, 0x3FFF);
+    assert_eq!(ppu.registers.v, 0x0000);
+    assert!(ppu.registers.w);
+
+    ppu.write_register(0x2006, 0x21, &mut cartridge);
+    assert_eq!(ppu.registers.t, 0x3F21);
+    assert_eq!(ppu.registers.v, 0x3F21);
+    assert!(!ppu.registers.w);
+}
+
+#[test]
+fn status_read_clears_vblank_and_the_write_toggle() {
+    let mut cartridge = cartridge(0);
+    let mut ppu = PPU::new();
+    ppu.registers.status = Registers::STATUS_VBLANK | Registers::STATUS_SPRITE_ZERO_HIT;
+    ppu.write_register(0x2005, 0x7D, &mut cartridge);
+    assert!(ppu.registers.w);
+
+    assert_eq!(ppu.read_register(0x2002, &mut cartridge) & 0xE0, 0xC0);
+    assert!(!ppu.registers.w);
+    assert_eq!(ppu.read_register(0x2002, &mut cartridge) & 0xE0, 0x40);
+
+    // The toggle starts over: this is a first write again.
+    ppu.write_register(0x2005, 0x08, &mut cartridge);
+    assert_eq!(ppu.registers.t & 0x001F, 0x01);
+}
+
+#[test]
+fn registers_are_mirrored_every_8_bytes() {
+    let mut bus = Bus::new(cartridge(0));
+
+    bus.write_byte(0x2FFE, 0x21);
+    bus.write_byte(0x3FFE, 0x08);
+    assert_eq!(bus.ppu.registers.v, 0x2108);
+
+    bus.write_byte(0x3FF8, 0x03);
+    assert_eq!(bus.ppu.registers.ctrl, 0x03);
+
+    bus.ppu.registers.status = Registers::STATUS_VBLANK;
+    assert_eq!(bus.read_byte(0x200A) & 0x80, 0x80);
+    assert!(!bus.ppu.registers.get_status(Registers::STATUS_VBLANK));
+}
+
+#[test]
+fn runs_three_dots_per_cpu_cycle() {
+    let mut bus = Bus::new(cartridge(0));
+
+    bus.tick(1);
+    assert_eq!((bus.ppu.scanline, bus.ppu.dot), (0, 3));
+
+    // 114 cycles are 342 dots, one more than a scanline.
+    bus.tick(113);
+    assert_eq!((bus.ppu.scanline, bus.ppu.dot), (1, 1));
+}
@@ -0,0 +1,234 @@
+use famines::{
+    cartridge::Cartridge,
+    ppu::{palette::PALETTE, registers::Registers, render::Sprite, PPU, SCREEN_WIDTH},
+};
+
+const BACKDROP: u8 = 0x0F;
+const BACKGROUND: u8 = 0x16;
+const BACKGROUND_ALT: u8 = 0x2A;
+const SPRITE: u8 = 0x12;
+const SPRITE_ALT: u8 = 0x30;
+
+// Tile 1 is solid colour 1, tile 2 solid colour 2 and tile 3 a single
+// column of colour 1 on its left edge.
+const SOLID_1: u8 = 1;
+const SOLID_2: u8 = 2;
+const LEFT_COLUMN: u8 = 3;
+
+/// NROM with 8KB of CHR-RAM, so patterns can be written through $2007.
+fn cartridge() -> Cartridge {
+    let mut raw = vec![b'N', b'E', b'S', 0x1A, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    raw.resize(16 + 16384, 0);
+    Cartridge::new(&raw).unwrap()
+}
+
+fn write(ppu: &mut PPU, cartridge: &mut Cartridge, address: u16, data: &[u8]) {
+    ppu.write_register(0x2006, (address >> 8) as u8, cartridge);
+    ppu.write_register(0x2006, address as u8, cartridge);
+    for &value in data {
+        ppu.write_register(0x2007, value, cartridge);
+    }
+}
+
+/// Writes the four OAM bytes of a sprite through OAMADDR/OAMDATA.
+fn sprite(ppu: &mut PPU, cartridge: &mut Cartridge, index: u8, bytes: [u8; 4]) {
+    ppu.write_register(0x2003, index * 4, cartridge);
+    for value in bytes {
+        ppu.write_register(0x2004, value, cartridge);
+    }
+}
+
+/// A PPU with the test patterns and palettes loaded, an empty nametable and
+/// every sprite hidden below the screen. Rendering is still disabled.
+fn setup(cartridge: &mut Cartridge) -> PPU {
+    let mut ppu = PPU::new();
+
+    write(&mut ppu, cartridge, SOLID_1 as u16 * 16, &[0xFF; 8]);
+    write(&mut ppu, cartridge, SOLID_2 as u16 * 16 + 8, &[0xFF; 8]);
+    write(&mut ppu, cartridge, LEFT_COLUMN as u16 * 16, &[0x80; 8]);
+
+    write(&mut ppu, cartridge, 0x3F00, &[BACKDROP, BACKGROUND]);
+    write(&mut ppu, cartridge, 0x3F05, &[BACKGROUND_ALT]);
+    write(&mut ppu, cartridge, 0x3F11, &[SPRITE]);
+    write(&mut ppu, cartridge, 0x3F16, &[SPRITE_ALT]);
+
+    for index in 0..64 {
+        sprite(&mut ppu, cartridge, index, [0xF0, 0, 0, 0]);
+    }
+    ppu
+}
+
+/// Points the scroll at the top-left of the first nametable and turns on
+/// background and sprite rendering, including the leftmost 8 pixels.
+fn enable_rendering(ppu: &mut PPU, cartridge: &mut Cartridge) {
+    ppu.write_register(0x2000, 0x00, cartridge);
+    ppu.write_register(0x2006, 0x00, cartridge);
+    ppu.write_register(0x2006, 0x00, cartridge);
+    ppu.write_register(0x2001, 0x1E, cartridge);
+}
+
+/// Renders the visible part of the frame, stopping at the start of vblank.
+fn run_frame(ppu: &mut PPU, cartridge: &mut Cartridge) {
+    while !ppu.poll_frame() {
+        ppu.tick(1, cartridge);
+    }
+}
+
+fn run_until(ppu: &mut PPU, cartridge: &mut Cartridge, scanline: usize, dot: usize) {
+    while (ppu.scanline, ppu.dot) != (scanline, dot) {
+        ppu.tick(1, cartridge);
+    }
+}
+
+fn pixel(ppu: &PPU, x: usize, y: usize) -> (u8, u8, u8) {
+    let offset = (y * SCREEN_WIDTH + x) * 3;
+    let frame = ppu.frame();
+    (frame[offset], frame[offset + 1], frame[offset + 2])
+}
+
+fn color(index: u8) -> (u8, u8, u8) {
+    PALETTE[index as usize]
+}
+
+#[test]
+fn draws_background_with_attribute_palettes() {
+    let mut cartridge = cartridge();
+    let mut ppu = setup(&mut cartridge);
+
+    // Tile (0, 0) uses palette 0. Tile (4, 0) is in the second attribute
+    // byte, whose top-left quadrant selects palette 1.
+    write(&mut ppu, &mut cartridge, 0x2000, &[SOLID_1]);
+    write(&mut ppu, &mut cartridge, 0x2004, &[SOLID_1]);
+    write(&mut ppu, &mut cartridge, 0x23C1, &[0b01]);
+    enable_rendering(&mut ppu, &mut cartridge);
+    run_frame(&mut ppu, &mut cartridge);
+
+    assert_eq!(pixel(&ppu, 0, 0), color(BACKGROUND));
+    assert_eq!(pixel(&ppu, 7, 7), color(BACKGROUND));
+    assert_eq!(pixel(&ppu, 8, 0), color(BACKDROP));
+    assert_eq!(pixel(&ppu, 0, 8), color(BACKDROP));
+    assert_eq!(pixel(&ppu, 32, 0), color(BACKGROUND_ALT));
+    assert_eq!(pixel(&ppu, 39, 7), color(BACKGROUND_ALT));
+    assert_eq!(pixel(&ppu, 255, 239), color(BACKDROP));
+}
+
+#[test]
+fn draws_sprites_one_line_below_their_y() {
+    let mut cartridge = cartridge();
+    let mut ppu = setup(&mut cartridge);
+
+    sprite(&mut ppu, &mut cartridge, 5, [31, SOLID_1, 0, 100]);
+    sprite(&mut ppu, &mut cartridge, 6, [31, SOLID_2, 0b01, 150]);
+    sprite(
+        &mut ppu,
+        &mut cartridge,
+        7,
+        [31, LEFT_COLUMN, Sprite::FLIP_HORIZONTAL, 200],
+    );
+    enable_rendering(&mut ppu, &mut cartridge);
+    run_frame(&mut ppu, &mut cartridge);
+
+    assert_eq!(pixel(&ppu, 100, 31), color(BACKDROP));
+    assert_eq!(pixel(&ppu, 100, 32), color(SPRITE));
+    assert_eq!(pixel(&ppu, 107, 39), color(SPRITE));
+    assert_eq!(pixel(&ppu, 108, 32), color(BACKDROP));
+    assert_eq!(pixel(&ppu, 100, 40), color(BACKDROP));
+
+    assert_eq!(pixel(&ppu, 150, 32), color(SPRITE_ALT));
+
+    assert_eq!(pixel(&ppu, 200, 32), color(BACKDROP));
+    assert_eq!(pixel(&ppu, 207, 32), color(SPRITE));
+}
+
+#[test]
+fn sprite_priority_against_background() {
+    let mut cartridge = cartridge();
+    let mut ppu = setup(&mut cartridge);
+
+    // Opaque background pixels at x = 0, 8 and 16 of the first tile row.
+    write(&mut ppu, &mut cartridge, 0x2000, &[LEFT_COLUMN; 3]);
+    // A sprite in front of the background, then one behind it.
+    sprite(&mut ppu, &mut cartridge, 1, [0, SOLID_1, 0, 0]);
+    sprite(
+        &mut ppu,
+        &mut cartridge,
+        2,
+        [0, SOLID_1, Sprite::BEHIND_BACKGROUND, 8],
+    );
+    // A sprite behind the background still hides a later sprite in front of it.
+    sprite(
+        &mut ppu,
+        &mut cartridge,
+        3,
+        [0, SOLID_1, Sprite::BEHIND_BACKGROUND, 16],
+    );
+    sprite(&mut ppu, &mut cartridge, 4, [0, SOLID_2, 0b01, 16]);
+    enable_rendering(&mut ppu, &mut cartridge);
+    run_frame(&mut ppu, &mut cartridge);
+
+    assert_eq!(pixel(&ppu, 0, 1), color(SPRITE));
+    assert_eq!(pixel(&ppu, 1, 1), color(SPRITE));
+    assert_eq!(pixel(&ppu, 8, 1), color(BACKGROUND));
+    assert_eq!(pixel(&ppu, 9, 1), color(SPRITE));
+    assert_eq!(pixel(&ppu, 16, 1), color(BACKGROUND));
+    assert_eq!(pixel(&ppu, 17, 1), color(SPRITE));
+}
+
+#[test]
+fn sprite_zero_hit_two_dots_after_the_overlap() {
+    let mut cartridge = cartridge();
+    let mut ppu = setup(&mut cartridge);
+
+    // A solid third tile row (scanlines 16-23) under sprite 0 at x = 40.
+    write(&mut ppu, &mut cartridge, 0x2040, &[SOLID_1; 32]);
+    sprite(&mut ppu, &mut cartridge, 0, [15, SOLID_1, 0, 40]);
+    enable_rendering(&mut ppu, &mut cartridge);
+
+    run_until(&mut ppu, &mut cartridge, 16, 42);
+    assert!(!ppu.registers.get_status(Registers::STATUS_SPRITE_ZERO_HIT));
+    ppu.tick(1, &mut cartridge);
+    assert!(ppu.registers.get_status(Registers::STATUS_SPRITE_ZERO_HIT));
+
+    // The flag stays set through vblank and is cleared on the pre-render line.
+    run_until(&mut ppu, &mut cartridge, 261, 1);
+    assert!(ppu.registers.get_status(Registers::STATUS_SPRITE_ZERO_HIT));
+    ppu.tick(1, &mut cartridge);
+    assert!(!ppu.registers.get_status(Registers::STATUS_SPRITE_ZERO_HIT));
+}
+
+#[test]
+fn sprite_zero_hit_needs_an_opaque_background() {
+    let mut cartridge = cartridge();
+    let mut ppu = setup(&mut cartridge);
+
+    sprite(&mut ppu, &mut cartridge, 0, [15, SOLID_1, 0, 40]);
+    enable_rendering(&mut ppu, &mut cartridge);
+    run_frame(&mut ppu, &mut cartridge);
+
+    assert!(!ppu.registers.get_status(Registers::STATUS_SPRITE_ZERO_HIT));
+}
+
+#[test]
+fn ninth_sprite_on_a_line_sets_overflow() {
+    let mut cartridge = cartridge();
+
+    for count in [8, 9] {
+        let mut ppu = setup(&mut cartridge);
+        for index in 0..count {
+            sprite(
+                &mut ppu,
+                &mut cartridge,
+                index,
+                [100, SOLID_1, 0, index * 24],
+            );
+        }
+        enable_rendering(&mut ppu, &mut cartridge);
+        run_frame(&mut ppu, &mut cartridge);
+
+        let overflow = ppu.registers.get_status(Registers::STATUS_SPRITE_OVERFLOW);
+        assert_eq!(overflow, count == 9);
+        // Only the first eight sprites are drawn.
+        assert_eq!(pixel(&ppu, 7 * 24, 101), color(SPRITE));
+        assert_eq!(pixel(&ppu, 8 * 24, 101), color(BACKDROP));
+    }
+}